@@ -0,0 +1,137 @@
+// Character names usable in `#\name` literals.
+const NAMES: &[(&str, char)] = &[
+    ("alarm", '\x07'),
+    ("backspace", '\x08'),
+    ("delete", '\x7f'),
+    ("escape", '\x1b'),
+    ("newline", '\n'),
+    ("null", '\0'),
+    ("return", '\r'),
+    ("space", ' '),
+    ("tab", '\t'),
+];
+
+// The first code point of every run of ten decimal digits (Unicode
+// category Nd), as of Unicode 14.0. char-numeric? and digit-value both use
+// it, so they stay in step when the standard library moves to a newer
+// version, which may add digits the table lacks.
+const DIGIT_ZEROS: &[u32] = &[
+    0x30, 0x660, 0x6F0, 0x7C0, 0x966, 0x9E6, 0xA66, 0xAE6, 0xB66, 0xBE6, 0xC66, 0xCE6, 0xD66,
+    0xDE6, 0xE50, 0xED0, 0xF20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80, 0x1A90,
+    0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x16A60, 0x16AC0,
+    0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E950, 0x1FBF0,
+];
+
+// The character written after `#\`: a single character, a name like
+// `space` or a hexadecimal scalar value like `x41`.
+pub fn parse(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+
+    if chars.next().is_none() {
+        return Some(first)
+    }
+
+    if let Some(&(_, chr)) = NAMES.iter().find(|&&(name, _)| name == text) {
+        return Some(chr)
+    }
+
+    match text.strip_prefix('x') {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        None => None
+    }
+}
+
+// The external representation of a character, including the `#\` prefix.
+pub fn write(chr: char) -> String {
+    match NAMES.iter().find(|&&(_, named)| named == chr) {
+        Some(&(name, _)) => format!("#\\{}", name),
+        None if chr.is_control() => format!("#\\x{:x}", chr as u32),
+        None => format!("#\\{}", chr)
+    }
+}
+
+// The value of a decimal digit from any script.
+pub fn digit_value(chr: char) -> Option<u32> {
+    let code = chr as u32;
+    DIGIT_ZEROS.iter()
+        .find(|&&zero| zero <= code && code < zero + 10)
+        .map(|&zero| code - zero)
+}
+
+// Case conversions keep the character as it is when its counterpart isn't a
+// single character, like the upper case of 'ß'.
+pub fn upcase(chr: char) -> char {
+    single(chr.to_uppercase()).unwrap_or(chr)
+}
+
+pub fn downcase(chr: char) -> char {
+    single(chr.to_lowercase()).unwrap_or(chr)
+}
+
+fn single<I: Iterator<Item = char>>(mut chars: I) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(chr), None) => Some(chr),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_characters() {
+        assert_eq!(parse("a"), Some('a'));
+        assert_eq!(parse("x"), Some('x'));
+        assert_eq!(parse("λ"), Some('λ'));
+        assert_eq!(parse("space"), Some(' '));
+        assert_eq!(parse("newline"), Some('\n'));
+        assert_eq!(parse("x41"), Some('A'));
+        assert_eq!(parse("x3bb"), Some('λ'));
+        assert_eq!(parse("xd800"), None);
+        assert_eq!(parse("spaces"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_write_characters() {
+        assert_eq!(write('a'), "#\\a");
+        assert_eq!(write(' '), "#\\space");
+        assert_eq!(write('\x7f'), "#\\delete");
+        assert_eq!(write('\x01'), "#\\x1");
+        assert_eq!(write('λ'), "#\\λ");
+    }
+
+    #[test]
+    fn test_digit_value() {
+        assert_eq!(digit_value('7'), Some(7));
+        assert_eq!(digit_value('\u{0664}'), Some(4));
+        assert_eq!(digit_value('\u{FF19}'), Some(9));
+        assert_eq!(digit_value('a'), None);
+        assert_eq!(digit_value('\u{00BD}'), None);
+    }
+
+    #[test]
+    fn test_digit_table() {
+        // The table is from Unicode 14.0, which the standard library covers.
+        assert!(char::UNICODE_VERSION >= (14, 0, 0));
+        assert!(DIGIT_ZEROS.windows(2).all(|pair| pair[0] + 10 <= pair[1]));
+        for &zero in DIGIT_ZEROS.iter() {
+            for code in zero..zero + 10 {
+                let chr = char::from_u32(code).unwrap();
+                assert!(chr.is_numeric(), "U+{:04X} isn't numeric", code);
+                assert_eq!(digit_value(chr), Some(code - zero));
+            }
+        }
+    }
+
+    #[test]
+    fn test_case_conversions() {
+        assert_eq!(upcase('λ'), 'Λ');
+        assert_eq!(downcase('Σ'), 'σ');
+        assert_eq!(upcase('ß'), 'ß');
+    }
+}
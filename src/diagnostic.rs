@@ -0,0 +1,246 @@
+use std::cmp;
+use std::fmt;
+
+use errors::ParseError;
+use errors::RuntimeError;
+use errors::SyntaxError;
+use span::Span;
+
+// An error along with what is needed to show it against the source it came
+// from: the span to underline, a label for the underlined part and a help
+// note suggesting a fix.
+pub struct Diagnostic {
+    pub title: String,
+    pub span: Option<Span>,
+    pub label: Option<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    // Renders the diagnostic the way compilers usually do, e.g.
+    //
+    //   RuntimeError: Identifier not found! Identifier("lenght")
+    //    --> <input>:1:2
+    //     |
+    //   1 | (lenght '(1 2))
+    //     |  ^^^^^^
+    //     = help: did you mean `length`?
+    //
+    // A span running over several lines has each of them underlined.
+    pub fn render(&self, source: &str) -> String {
+        let span = match self.span {
+            Some(ref span) => span,
+            None => {
+                return match self.help {
+                    Some(ref help) => format!("{}\n  = help: {}", self.title, help),
+                    None => self.title.clone()
+                }
+            }
+        };
+
+        let text = source.get(span.start..span.end).unwrap_or("");
+        let last_line = span.line + text.matches('\x0a').count();
+        let end_column = match text.rfind('\x0a') {
+            Some(newline) => text[newline + 1..].chars().count(),
+            None => span.column + text.chars().count()
+        };
+
+        let pad = " ".repeat(last_line.to_string().len());
+        let mut output = format!(
+            "{}\n{}--> {}:{}:{}\n{} |", self.title, pad, span.file, span.line, span.column + 1, pad
+        );
+
+        for line in span.line..=last_line {
+            let code = source.lines().nth(line - 1).unwrap_or("").replace('\x09', " ");
+            let from = if line == span.line { span.column } else { 0 };
+            let to = if line == last_line { end_column } else { code.chars().count() };
+
+            let carets = "^".repeat(cmp::max(1, to.saturating_sub(from)));
+            output.push_str(&format!("\n{:>width$} | {}", line, code, width = pad.len()));
+            output.push_str(&format!("\n{} | {}{}", pad, " ".repeat(from), carets));
+            if let (true, Some(label)) = (line == last_line, self.label.as_ref()) {
+                output.push_str(&format!(" {}", label));
+            }
+        }
+
+        if let Some(ref help) = self.help {
+            output.push_str(&format!("\n{} = help: {}", pad, help));
+        }
+
+        output
+    }
+}
+
+// The one-line form of the diagnostic, the same as the error it comes from.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(ref span) => write!(f, "{} ({})", self.title, span),
+            None => write!(f, "{}", self.title)
+        }
+    }
+}
+
+impl From<SyntaxError> for Diagnostic {
+    fn from(error: SyntaxError) -> Diagnostic {
+        Diagnostic {
+            title: format!("SyntaxError: {}", error.message()),
+            span: error.span().cloned(),
+            label: None,
+            help: None
+        }
+    }
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(error: ParseError) -> Diagnostic {
+        let label = match error {
+            ParseError::ClosingParenWithoutOpening(_) => Some("this paren is never opened"),
+            ParseError::UnexpectedEOI(_) => Some("this paren is never closed"),
+            ParseError::MissingDatum(..) => Some("expected a datum after this"),
+            _ => None
+        };
+
+        Diagnostic {
+            title: format!("ParseError: {}", error.message()),
+            span: Some(error.span().clone()),
+            label: label.map(|label| label.to_string()),
+            help: None
+        }
+    }
+}
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(error: RuntimeError) -> Diagnostic {
+        Diagnostic {
+            title: format!("RuntimeError: {}", error.message),
+            span: error.span,
+            label: None,
+            help: error.help
+        }
+    }
+}
+
+// The number of characters to insert, delete or replace to turn one string
+// into the other (the Levenshtein distance).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_chr) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_chr) in b.iter().enumerate() {
+            let replaced = diagonal + if a_chr == b_chr { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = cmp::min(replaced, cmp::min(row[j], row[j + 1]) + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+// The candidate closest to the name, if it is close enough to be what was
+// meant: a third of the name's characters, or one for shorter names, may
+// differ. Ties go to the first candidate in alphabetical order.
+pub fn closest<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+    where I: IntoIterator<Item = &'a str> {
+    let limit = cmp::max(1, name.chars().count() / 3);
+
+    candidates.into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use errors::ParseError;
+    use errors::RuntimeError;
+    use span::Span;
+
+    use super::closest;
+    use super::edit_distance;
+    use super::Diagnostic;
+
+    fn span(line: usize, column: usize, start: usize, end: usize) -> Span {
+        Span { file: Rc::new("<input>".to_string()), line, column, start, end }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("define", "define"), 0);
+        assert_eq!(edit_distance("defin", "define"), 1);
+        assert_eq!(edit_distance("lenght", "length"), 2);
+        assert_eq!(edit_distance("", "car"), 3);
+        assert_eq!(edit_distance("λx", "λy"), 1);
+
+        assert_eq!(closest("defien", vec!["define", "delay", "if"]), Some("define"));
+        assert_eq!(closest("cdr", vec!["car", "cdr"]), Some("cdr"));
+        assert_eq!(closest("cbr", vec!["cdr", "car"]), Some("car"));
+        assert_eq!(closest("foo", vec!["define", "lambda"]), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let error = RuntimeError {
+            message: "Identifier not found! Identifier(\"lenght\")".to_string(),
+            span: Some(span(2, 1, 11, 17)),
+            help: Some("did you mean `length`?".to_string())
+        };
+        let diagnostic = Diagnostic::from(error);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "RuntimeError: Identifier not found! Identifier(\"lenght\") (line: 2, column: 2)"
+        );
+        assert_eq!(
+            diagnostic.render("(define l\n(lenght '(1 2)))"),
+            "RuntimeError: Identifier not found! Identifier(\"lenght\")\n \
+             --> <input>:2:2\n  \
+              |\n\
+             2 | (lenght '(1 2)))\n  \
+              |  ^^^^^^\n  \
+              = help: did you mean `length`?"
+        );
+    }
+
+    #[test]
+    fn test_render_unclosed_paren() {
+        let source = "(define (f x)\n  (+ x 1)";
+        let diagnostic = Diagnostic::from(ParseError::UnexpectedEOI(span(1, 0, 0, 1)));
+
+        assert_eq!(
+            diagnostic.render(source),
+            "ParseError: Unexpected end of input, a paren is not closed\n \
+             --> <input>:1:1\n  \
+              |\n\
+             1 | (define (f x)\n  \
+              | ^ this paren is never closed"
+        );
+    }
+
+    #[test]
+    fn test_render_multiline_span() {
+        let error = RuntimeError {
+            message: "Bad form".to_string(),
+            span: Some(span(9, 2, 10, 25)),
+            help: None
+        };
+        let source = "\n".repeat(8) + "  (if #t\n      1)";
+
+        assert_eq!(
+            Diagnostic::from(error).render(&source),
+            "RuntimeError: Bad form\n  \
+               --> <input>:9:3\n   \
+                |\n \
+              9 |   (if #t\n   \
+                |   ^^^^^^\n\
+             10 |       1)\n   \
+                | ^^^^^^^^"
+        );
+    }
+}
@@ -1,72 +1,119 @@
 use std::fmt;
 
+use span::Span;
+
 // SyntaxError can be raised when the input program is being tokenized:
 
 pub enum SyntaxError {
-    InvalidSymbol(usize, usize, String),
-    StringNotClosed,
+    InvalidSymbol(Span, String),
+    StringNotClosed(Span),
+    CommentNotClosed(Span),
     UnexpectedEOL
 }
 
-impl fmt::Display for SyntaxError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &SyntaxError::InvalidSymbol(line, column, ref message) => {
-                write!(
-                    f,
-                    "SyntaxError: {} (line: {}, column: {})",
-                    message, line, column + 1
-                )
+impl SyntaxError {
+    // The message, without the location the error was raised at.
+    pub fn message(&self) -> String {
+        match *self {
+            SyntaxError::InvalidSymbol(_, ref message) => message.clone(),
+            SyntaxError::StringNotClosed(_) => {
+                "String literal is not properly closed".to_string()
             },
-            &SyntaxError::StringNotClosed => {
-                write!(f, "SyntaxError: String literal is not properly closed")
+            SyntaxError::CommentNotClosed(_) => {
+                "Block comment is not properly closed".to_string()
             },
-            &SyntaxError::UnexpectedEOL => {
-                write!(f, "SyntaxError: Unexpected end of input")
-            }
+            SyntaxError::UnexpectedEOL => "Unexpected end of input".to_string()
+        }
+    }
+
+    // Unclosed strings and comments are placed at the characters opening them.
+    pub fn span(&self) -> Option<&Span> {
+        match *self {
+            SyntaxError::InvalidSymbol(ref span, _) |
+            SyntaxError::StringNotClosed(ref span) |
+            SyntaxError::CommentNotClosed(ref span) => Some(span),
+            SyntaxError::UnexpectedEOL => None
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span() {
+            Some(span) => write!(f, "SyntaxError: {} ({})", self.message(), span),
+            None => write!(f, "SyntaxError: {}", self.message())
         }
     }
 }
 
 impl fmt::Debug for SyntaxError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 
 #[macro_export]
 macro_rules! invalid_symbol_error(
-    ($line:ident, $column:ident, $($arg:tt)*) => (
-        return Err(SyntaxError::InvalidSymbol($line, $column, format!($($arg)*)))
+    ($span:ident, $($arg:tt)*) => (
+        return Err(SyntaxError::InvalidSymbol($span, format!($($arg)*)))
     )
 );
 
 // SyntaxError END
 
 // ParseError can be raised when the AST is being built:
+// Each error carries the span of the token it was raised at, an unexpected
+// end of input the one of the paren left open.
 pub enum ParseError {
-    ClosingParenWithoutOpening,
-    UnexpectedEOI
+    ClosingParenWithoutOpening(Span),
+    UnexpectedEOI(Span),
+    MissingDatum(String, Span),
+    UnexpectedDot(Span),
+    InvalidByte(String, Span)
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &ParseError::ClosingParenWithoutOpening => {
-                write!(
-                    f, "ParseError: Closing parens don't match the opening ones"
-                )
+impl ParseError {
+    // The message, without the location the error was raised at.
+    pub fn message(&self) -> String {
+        match *self {
+            ParseError::ClosingParenWithoutOpening(_) => {
+                "Closing parens don't match the opening ones".to_string()
             },
-            &ParseError::UnexpectedEOI => {
-                write!(f, "ParseError: Unexpected end of input")
+            ParseError::UnexpectedEOI(_) => {
+                "Unexpected end of input, a paren is not closed".to_string()
+            },
+            ParseError::MissingDatum(ref prefix, _) => {
+                format!("Expected a datum after {}", prefix)
+            },
+            ParseError::UnexpectedDot(_) => {
+                "Unexpected '.', it can only come before the last datum of a list".to_string()
+            },
+            ParseError::InvalidByte(ref element, _) => {
+                format!("Bytevector elements must be integers from 0 to 255: {}", element)
             }
         }
     }
+
+    pub fn span(&self) -> &Span {
+        match *self {
+            ParseError::ClosingParenWithoutOpening(ref span) |
+            ParseError::UnexpectedEOI(ref span) |
+            ParseError::MissingDatum(_, ref span) |
+            ParseError::UnexpectedDot(ref span) |
+            ParseError::InvalidByte(_, ref span) => span
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ParseError: {} ({})", self.message(), self.span())
+    }
 }
 
 impl fmt::Debug for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 
@@ -74,26 +121,41 @@ impl fmt::Debug for ParseError {
 
 // RuntimeError can be raised when we evaluate the AST:
 
+// The span is the one of the innermost expression whose evaluation failed.
+// The help, if any, suggests a fix and is only shown in diagnostics.
 pub struct RuntimeError {
     pub message: String,
+    pub span: Option<Span>,
+    pub help: Option<String>,
+}
+
+impl RuntimeError {
+    // Places the error in the span, unless it was already placed in a
+    // narrower one.
+    pub fn at(self, span: &Span) -> RuntimeError {
+        RuntimeError { span: self.span.or_else(|| Some(span.clone())), ..self }
+    }
 }
 
 impl fmt::Display for RuntimeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "RuntimeError: {}", self.message)
+        match self.span {
+            Some(ref span) => write!(f, "RuntimeError: {} ({})", self.message, span),
+            None => write!(f, "RuntimeError: {}", self.message)
+        }
     }
 }
 
 impl fmt::Debug for RuntimeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 
 #[macro_export]
 macro_rules! runtime_error(
     ($($arg:tt)*) => (
-        return Err(RuntimeError { message: format!($($arg)*) })
+        return Err(RuntimeError { message: format!($($arg)*), span: None, help: None })
     )
 );
 
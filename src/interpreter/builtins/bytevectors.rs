@@ -0,0 +1,144 @@
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::builtins::byte;
+use interpreter::builtins::bytes;
+use interpreter::builtins::filled;
+use interpreter::builtins::index;
+use interpreter::builtins::integer;
+use interpreter::builtins::range;
+use interpreter::builtins::string_chars;
+use interpreter::value::Builtin;
+use interpreter::value::Shared;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "bytevector?", min_args: 1, max_args: Some(1), func: is_bytevector },
+    Builtin { name: "make-bytevector", min_args: 1, max_args: Some(2), func: make_bytevector },
+    Builtin { name: "bytevector", min_args: 0, max_args: None, func: bytevector },
+    Builtin { name: "bytevector-length", min_args: 1, max_args: Some(1), func: bytevector_length },
+    Builtin { name: "bytevector-u8-ref", min_args: 2, max_args: Some(2), func: u8_ref },
+    Builtin { name: "bytevector-u8-set!", min_args: 3, max_args: Some(3), func: u8_set },
+    Builtin { name: "bytevector-copy", min_args: 1, max_args: Some(3), func: bytevector_copy },
+    Builtin { name: "bytevector-copy!", min_args: 3, max_args: Some(5), func: bytevector_copy_to },
+    Builtin { name: "bytevector-append", min_args: 0, max_args: None, func: bytevector_append },
+    Builtin { name: "utf8->string", min_args: 1, max_args: Some(3), func: utf8_to_string },
+    Builtin { name: "string->utf8", min_args: 1, max_args: Some(3), func: string_to_utf8 },
+];
+
+// A bytevector which can be modified; literals are constants.
+fn mutable_bytes(name: &str, value: &Value) -> Result<Rc<Shared<Vec<u8>>>, RuntimeError> {
+    let bytes = bytes(name, value)?;
+    if !bytes.mutable {
+        runtime_error!("Can't modify a bytevector literal with '{}': {:?}", name, value)
+    }
+    Ok(bytes)
+}
+
+fn is_bytevector(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Bytevector(_))))
+}
+
+fn make_bytevector(args: &[Value]) -> Result<Value, RuntimeError> {
+    let length = integer("make-bytevector", &args[0])?;
+    if length < 0 {
+        runtime_error!("Bad length for 'make-bytevector': {}", length)
+    }
+
+    let fill = match args.get(1) {
+        Some(value) => byte("make-bytevector", value)?,
+        None => 0
+    };
+    Ok(Value::bytevector(filled("make-bytevector", length as usize, fill)?))
+}
+
+fn bytevector(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        result.push(byte("bytevector", arg)?)
+    }
+    Ok(Value::bytevector(result))
+}
+
+fn bytevector_length(args: &[Value]) -> Result<Value, RuntimeError> {
+    let length = bytes("bytevector-length", &args[0])?.value.borrow().len();
+    Ok(Number(Number::Integer(length as i64)))
+}
+
+fn u8_ref(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = bytes("bytevector-u8-ref", &args[0])?;
+    let index = integer("bytevector-u8-ref", &args[1])?;
+
+    let bytes = bytes.value.borrow();
+    match usize::try_from(index).ok().and_then(|index| bytes.get(index)) {
+        Some(&byte) => Ok(Number(Number::Integer(byte as i64))),
+        None => runtime_error!("Index out of range for 'bytevector-u8-ref': {}", index)
+    }
+}
+
+fn u8_set(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = mutable_bytes("bytevector-u8-set!", &args[0])?;
+    let index = integer("bytevector-u8-set!", &args[1])?;
+    let byte = byte("bytevector-u8-set!", &args[2])?;
+
+    let mut bytes = bytes.value.borrow_mut();
+    match usize::try_from(index).ok().and_then(|index| bytes.get_mut(index)) {
+        Some(place) => *place = byte,
+        None => runtime_error!("Index out of range for 'bytevector-u8-set!': {}", index)
+    }
+    Ok(empty!())
+}
+
+fn bytevector_copy(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = bytes("bytevector-copy", &args[0])?;
+    let bytes = bytes.value.borrow();
+    let (start, end) = range("bytevector-copy", args, 1, bytes.len())?;
+    Ok(Value::bytevector(bytes[start..end].to_vec()))
+}
+
+// Copies the bytes of `from` between the optional start and end into `to`,
+// starting at the index `at`. The bytevectors may be the same one.
+fn bytevector_copy_to(args: &[Value]) -> Result<Value, RuntimeError> {
+    let to = mutable_bytes("bytevector-copy!", &args[0])?;
+    let from = bytes("bytevector-copy!", &args[2])?.value.borrow().clone();
+    let mut to = to.value.borrow_mut();
+    let at = index("bytevector-copy!", &args[1], to.len())?;
+    let (start, end) = range("bytevector-copy!", args, 3, from.len())?;
+
+    if end - start > to.len() - at {
+        runtime_error!("Not enough room for 'bytevector-copy!' to copy {} bytes at {}", end - start, at)
+    }
+
+    to[at..at + end - start].copy_from_slice(&from[start..end]);
+    Ok(empty!())
+}
+
+fn bytevector_append(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = vec![];
+    for arg in args.iter() {
+        result.extend_from_slice(&bytes("bytevector-append", arg)?.value.borrow())
+    }
+    Ok(Value::bytevector(result))
+}
+
+fn utf8_to_string(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = bytes("utf8->string", &args[0])?;
+    let bytes = bytes.value.borrow();
+    let (start, end) = range("utf8->string", args, 1, bytes.len())?;
+
+    match String::from_utf8(bytes[start..end].to_vec()) {
+        Ok(result) => Ok(Value::string(result.chars().collect())),
+        Err(_) => runtime_error!("Invalid UTF-8 for 'utf8->string': {:?}", args[0])
+    }
+}
+
+fn string_to_utf8(args: &[Value]) -> Result<Value, RuntimeError> {
+    let chars = string_chars("string->utf8", &args[0])?;
+    let chars = chars.value.borrow();
+    let (start, end) = range("string->utf8", args, 1, chars.len())?;
+    Ok(Value::bytevector(chars[start..end].iter().collect::<String>().into_bytes()))
+}
@@ -0,0 +1,148 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+use character;
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::builtins::character;
+use interpreter::builtins::integer;
+use interpreter::value::Builtin;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "char?", min_args: 1, max_args: Some(1), func: is_char },
+    Builtin { name: "char->integer", min_args: 1, max_args: Some(1), func: char_to_integer },
+    Builtin { name: "integer->char", min_args: 1, max_args: Some(1), func: integer_to_char },
+    Builtin { name: "char-upcase", min_args: 1, max_args: Some(1), func: upcase },
+    Builtin { name: "char-downcase", min_args: 1, max_args: Some(1), func: downcase },
+    Builtin { name: "char-foldcase", min_args: 1, max_args: Some(1), func: foldcase },
+    Builtin { name: "char-alphabetic?", min_args: 1, max_args: Some(1), func: is_alphabetic },
+    Builtin { name: "char-numeric?", min_args: 1, max_args: Some(1), func: is_numeric },
+    Builtin { name: "char-whitespace?", min_args: 1, max_args: Some(1), func: is_whitespace },
+    Builtin { name: "char-upper-case?", min_args: 1, max_args: Some(1), func: is_upper_case },
+    Builtin { name: "char-lower-case?", min_args: 1, max_args: Some(1), func: is_lower_case },
+    Builtin { name: "digit-value", min_args: 1, max_args: Some(1), func: digit_value },
+    Builtin { name: "char=?", min_args: 1, max_args: None, func: equal },
+    Builtin { name: "char<?", min_args: 1, max_args: None, func: less },
+    Builtin { name: "char>?", min_args: 1, max_args: None, func: greater },
+    Builtin { name: "char<=?", min_args: 1, max_args: None, func: less_or_equal },
+    Builtin { name: "char>=?", min_args: 1, max_args: None, func: greater_or_equal },
+    Builtin { name: "char-ci=?", min_args: 1, max_args: None, func: equal_ci },
+    Builtin { name: "char-ci<?", min_args: 1, max_args: None, func: less_ci },
+    Builtin { name: "char-ci>?", min_args: 1, max_args: None, func: greater_ci },
+    Builtin { name: "char-ci<=?", min_args: 1, max_args: None, func: less_or_equal_ci },
+    Builtin { name: "char-ci>=?", min_args: 1, max_args: None, func: greater_or_equal_ci },
+];
+
+fn is_char(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Character(_))))
+}
+
+fn char_to_integer(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(Number::Integer(character("char->integer", &args[0])? as i64)))
+}
+
+fn integer_to_char(args: &[Value]) -> Result<Value, RuntimeError> {
+    let code = integer("integer->char", &args[0])?;
+    match u32::try_from(code).ok().and_then(char::from_u32) {
+        Some(chr) => Ok(Character(chr)),
+        None => runtime_error!(
+            "Bad argument for 'integer->char', expected a Unicode scalar value: {}", code
+        )
+    }
+}
+
+fn upcase(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Character(character::upcase(character("char-upcase", &args[0])?)))
+}
+
+fn downcase(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Character(character::downcase(character("char-downcase", &args[0])?)))
+}
+
+fn foldcase(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Character(character::downcase(character("char-foldcase", &args[0])?)))
+}
+
+fn is_alphabetic(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(character("char-alphabetic?", &args[0])?.is_alphabetic()))
+}
+
+fn is_numeric(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(character::digit_value(character("char-numeric?", &args[0])?).is_some()))
+}
+
+fn is_whitespace(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(character("char-whitespace?", &args[0])?.is_whitespace()))
+}
+
+fn is_upper_case(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(character("char-upper-case?", &args[0])?.is_uppercase()))
+}
+
+fn is_lower_case(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(character("char-lower-case?", &args[0])?.is_lowercase()))
+}
+
+fn digit_value(args: &[Value]) -> Result<Value, RuntimeError> {
+    match character::digit_value(character("digit-value", &args[0])?) {
+        Some(digit) => Ok(Number(Number::Integer(digit as i64))),
+        None => Ok(Boolean(false))
+    }
+}
+
+// Checks that the ordering holds between every pair of neighbouring
+// arguments, comparing their case folded versions if `fold` is set.
+fn comparison(
+    name: &str, args: &[Value], fold: bool, holds: fn(Ordering) -> bool
+) -> Result<Value, RuntimeError> {
+    let mut chars = vec![];
+    for arg in args.iter() {
+        let chr = character(name, arg)?;
+        chars.push(if fold { character::downcase(chr) } else { chr });
+    }
+
+    Ok(Boolean(chars.windows(2).all(|pair| holds(pair[0].cmp(&pair[1])))))
+}
+
+fn equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char=?", args, false, |ordering| ordering == Ordering::Equal)
+}
+
+fn less(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char<?", args, false, |ordering| ordering == Ordering::Less)
+}
+
+fn greater(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char>?", args, false, |ordering| ordering == Ordering::Greater)
+}
+
+fn less_or_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char<=?", args, false, |ordering| ordering != Ordering::Greater)
+}
+
+fn greater_or_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char>=?", args, false, |ordering| ordering != Ordering::Less)
+}
+
+fn equal_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char-ci=?", args, true, |ordering| ordering == Ordering::Equal)
+}
+
+fn less_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char-ci<?", args, true, |ordering| ordering == Ordering::Less)
+}
+
+fn greater_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char-ci>?", args, true, |ordering| ordering == Ordering::Greater)
+}
+
+fn less_or_equal_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char-ci<=?", args, true, |ordering| ordering != Ordering::Greater)
+}
+
+fn greater_or_equal_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("char-ci>=?", args, true, |ordering| ordering != Ordering::Less)
+}
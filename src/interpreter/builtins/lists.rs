@@ -0,0 +1,122 @@
+use std::rc::Rc;
+
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::builtins::integer;
+use interpreter::value::Builtin;
+use interpreter::value::Cons;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "cons", min_args: 2, max_args: Some(2), func: cons },
+    Builtin { name: "car", min_args: 1, max_args: Some(1), func: car },
+    Builtin { name: "cdr", min_args: 1, max_args: Some(1), func: cdr },
+    Builtin { name: "set-car!", min_args: 2, max_args: Some(2), func: set_car },
+    Builtin { name: "set-cdr!", min_args: 2, max_args: Some(2), func: set_cdr },
+    Builtin { name: "list", min_args: 0, max_args: None, func: list },
+    Builtin { name: "null?", min_args: 1, max_args: Some(1), func: is_null },
+    Builtin { name: "pair?", min_args: 1, max_args: Some(1), func: is_pair },
+    Builtin { name: "list?", min_args: 1, max_args: Some(1), func: is_list },
+    Builtin { name: "length", min_args: 1, max_args: Some(1), func: length },
+    Builtin { name: "append", min_args: 0, max_args: None, func: append },
+    Builtin { name: "reverse", min_args: 1, max_args: Some(1), func: reverse },
+    Builtin { name: "list-ref", min_args: 2, max_args: Some(2), func: list_ref },
+];
+
+fn pair<'a>(name: &str, value: &'a Value) -> Result<&'a Rc<Cons>, RuntimeError> {
+    match *value {
+        Pair(ref pair) => Ok(pair),
+        _ => runtime_error!("Bad argument for '{}', expected a pair: {:?}", name, value)
+    }
+}
+
+fn proper_list(name: &str, value: &Value) -> Result<Vec<Value>, RuntimeError> {
+    match value.list_to_vec() {
+        Some(values) => Ok(values),
+        None => runtime_error!("Bad argument for '{}', expected a list: {:?}", name, value)
+    }
+}
+
+fn cons(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::cons(args[0].clone(), args[1].clone()))
+}
+
+fn car(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(pair("car", &args[0])?.car.borrow().clone())
+}
+
+fn cdr(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(pair("cdr", &args[0])?.cdr.borrow().clone())
+}
+
+fn set_car(args: &[Value]) -> Result<Value, RuntimeError> {
+    *pair("set-car!", &args[0])?.car.borrow_mut() = args[1].clone();
+    Ok(empty!())
+}
+
+fn set_cdr(args: &[Value]) -> Result<Value, RuntimeError> {
+    *pair("set-cdr!", &args[0])?.cdr.borrow_mut() = args[1].clone();
+    Ok(empty!())
+}
+
+fn list(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::list(args.to_vec()))
+}
+
+fn is_null(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Nil)))
+}
+
+fn is_pair(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Pair(_))))
+}
+
+fn is_list(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(args[0].is_list()))
+}
+
+fn length(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(Number::Integer(proper_list("length", &args[0])?.len() as i64)))
+}
+
+fn append(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (last, init) = match args.split_last() {
+        Some(split) => split,
+        None => return Ok(Nil)
+    };
+
+    let mut values = vec![];
+    for arg in init.iter() {
+        values.extend(proper_list("append", arg)?);
+    }
+    Ok(Value::list_with_tail(values, last.clone()))
+}
+
+fn reverse(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut values = proper_list("reverse", &args[0])?;
+    values.reverse();
+    Ok(Value::list(values))
+}
+
+fn list_ref(args: &[Value]) -> Result<Value, RuntimeError> {
+    let index = integer("list-ref", &args[1])?;
+    if index < 0 {
+        runtime_error!("Bad index for 'list-ref': {}", index)
+    }
+
+    let mut current = args[0].clone();
+    for _ in 0..index {
+        let next = match current {
+            Pair(ref pair) => pair.cdr.borrow().clone(),
+            _ => runtime_error!("Index out of range for 'list-ref': {}", index)
+        };
+        current = next;
+    }
+
+    match current {
+        Pair(ref pair) => Ok(pair.car.borrow().clone()),
+        _ => runtime_error!("Index out of range for 'list-ref': {}", index)
+    }
+}
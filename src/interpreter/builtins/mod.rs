@@ -0,0 +1,134 @@
+mod bytevectors;
+mod chars;
+mod lists;
+mod numbers;
+mod ports;
+mod predicates;
+mod strings;
+mod vectors;
+
+use std::rc::Rc;
+
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::runtime::Runtime;
+use interpreter::value::Builtin;
+use interpreter::value::Shared;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub fn define_builtins(runtime: &mut Runtime) {
+    let tables: &[&[Builtin]] = &[
+        BUILTINS,
+        predicates::BUILTINS,
+        numbers::BUILTINS,
+        lists::BUILTINS,
+        chars::BUILTINS,
+        strings::BUILTINS,
+        vectors::BUILTINS,
+        bytevectors::BUILTINS,
+        ports::BUILTINS,
+    ];
+
+    for builtin in tables.iter().flat_map(|table| table.iter()) {
+        runtime.set_var_value(builtin.name.to_string(), Native(*builtin));
+    }
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin { name: "error", min_args: 1, max_args: Some(1), func: error },
+];
+
+fn error(args: &[Value]) -> Result<Value, RuntimeError> {
+    runtime_error!("{}", args[0])
+}
+
+pub fn number<'a>(name: &str, value: &'a Value) -> Result<&'a Number, RuntimeError> {
+    match *value {
+        Number(ref x) => Ok(x),
+        _ => runtime_error!("Bad argument for '{}', expected a number: {:?}", name, value)
+    }
+}
+
+// An exact integer small enough to be used as an index or a count.
+pub fn integer(name: &str, value: &Value) -> Result<i64, RuntimeError> {
+    match *value {
+        Number(Number::Integer(x)) => Ok(x),
+        _ => runtime_error!("Bad argument for '{}', expected an exact integer: {:?}", name, value)
+    }
+}
+
+// An index into a sequence of the given length. The length itself is a
+// valid index, as the end of a range.
+pub fn index(name: &str, value: &Value, len: usize) -> Result<usize, RuntimeError> {
+    let index = integer(name, value)?;
+    if index < 0 || index as usize > len {
+        runtime_error!("Index out of range for '{}': {}", name, index)
+    }
+    Ok(index as usize)
+}
+
+// The optional start and end indices starting at the given argument,
+// defaulting to the whole sequence.
+pub fn range(
+    name: &str, args: &[Value], from: usize, len: usize
+) -> Result<(usize, usize), RuntimeError> {
+    let start = match args.get(from) {
+        Some(value) => index(name, value, len)?,
+        None => 0
+    };
+    let end = match args.get(from + 1) {
+        Some(value) => index(name, value, len)?,
+        None => len
+    };
+
+    if start > end {
+        runtime_error!("Bad range for '{}': {} to {}", name, start, end)
+    }
+    Ok((start, end))
+}
+
+// A sequence of `length` copies of `fill`, for the procedures making a new
+// one. A length too large to allocate is an error rather than an abort.
+pub fn filled<T: Clone>(name: &str, length: usize, fill: T) -> Result<Vec<T>, RuntimeError> {
+    let mut result = Vec::new();
+    if result.try_reserve_exact(length).is_err() {
+        runtime_error!("Length too large for '{}': {}", name, length)
+    }
+    result.resize(length, fill);
+    Ok(result)
+}
+
+pub fn byte(name: &str, value: &Value) -> Result<u8, RuntimeError> {
+    match *value {
+        Number(Number::Integer(x)) if (0..=255).contains(&x) => Ok(x as u8),
+        _ => runtime_error!("Bad argument for '{}', expected a byte: {:?}", name, value)
+    }
+}
+
+pub fn bytes(name: &str, value: &Value) -> Result<Rc<Shared<Vec<u8>>>, RuntimeError> {
+    match *value {
+        Bytevector(ref bytes) => Ok(bytes.clone()),
+        _ => runtime_error!("Bad argument for '{}', expected a bytevector: {:?}", name, value)
+    }
+}
+
+pub fn character(name: &str, value: &Value) -> Result<char, RuntimeError> {
+    match *value {
+        Character(x) => Ok(x),
+        _ => runtime_error!("Bad argument for '{}', expected a character: {:?}", name, value)
+    }
+}
+
+pub fn string(name: &str, value: &Value) -> Result<String, RuntimeError> {
+    Ok(string_chars(name, value)?.value.borrow().iter().collect())
+}
+
+// The characters of a string, shared rather than copied.
+pub fn string_chars(name: &str, value: &Value) -> Result<Rc<Shared<Vec<char>>>, RuntimeError> {
+    match *value {
+        StringValue(ref x) => Ok(x.clone()),
+        _ => runtime_error!("Bad argument for '{}', expected a string: {:?}", name, value)
+    }
+}
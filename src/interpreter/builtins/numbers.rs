@@ -0,0 +1,401 @@
+use std::cmp::Ordering;
+use std::f64;
+
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::builtins::integer;
+use interpreter::builtins::number;
+use interpreter::builtins::string;
+use interpreter::value::Builtin;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "+", min_args: 0, max_args: None, func: plus },
+    Builtin { name: "-", min_args: 1, max_args: None, func: minus },
+    Builtin { name: "*", min_args: 0, max_args: None, func: multiplication },
+    Builtin { name: "/", min_args: 1, max_args: None, func: division },
+    Builtin { name: "=", min_args: 1, max_args: None, func: equals },
+    Builtin { name: "<", min_args: 1, max_args: None, func: less },
+    Builtin { name: ">", min_args: 1, max_args: None, func: greater },
+    Builtin { name: "<=", min_args: 1, max_args: None, func: less_or_equal },
+    Builtin { name: ">=", min_args: 1, max_args: None, func: greater_or_equal },
+    Builtin { name: "number?", min_args: 1, max_args: Some(1), func: is_number },
+    Builtin { name: "integer?", min_args: 1, max_args: Some(1), func: is_integer },
+    Builtin { name: "zero?", min_args: 1, max_args: Some(1), func: is_zero },
+    Builtin { name: "positive?", min_args: 1, max_args: Some(1), func: is_positive },
+    Builtin { name: "negative?", min_args: 1, max_args: Some(1), func: is_negative },
+    Builtin { name: "even?", min_args: 1, max_args: Some(1), func: is_even },
+    Builtin { name: "odd?", min_args: 1, max_args: Some(1), func: is_odd },
+    Builtin { name: "quotient", min_args: 2, max_args: Some(2), func: quotient },
+    Builtin { name: "remainder", min_args: 2, max_args: Some(2), func: remainder },
+    Builtin { name: "modulo", min_args: 2, max_args: Some(2), func: modulo },
+    Builtin { name: "floor/", min_args: 2, max_args: Some(2), func: floor_division },
+    Builtin { name: "truncate/", min_args: 2, max_args: Some(2), func: truncate_division },
+    Builtin { name: "abs", min_args: 1, max_args: Some(1), func: abs },
+    Builtin { name: "min", min_args: 1, max_args: None, func: min },
+    Builtin { name: "max", min_args: 1, max_args: None, func: max },
+    Builtin { name: "gcd", min_args: 0, max_args: None, func: gcd },
+    Builtin { name: "lcm", min_args: 0, max_args: None, func: lcm },
+    Builtin { name: "floor", min_args: 1, max_args: Some(1), func: floor },
+    Builtin { name: "ceiling", min_args: 1, max_args: Some(1), func: ceiling },
+    Builtin { name: "round", min_args: 1, max_args: Some(1), func: round },
+    Builtin { name: "truncate", min_args: 1, max_args: Some(1), func: truncate },
+    Builtin { name: "expt", min_args: 2, max_args: Some(2), func: expt },
+    Builtin { name: "exact-integer-sqrt", min_args: 1, max_args: Some(1), func: exact_integer_sqrt },
+    Builtin { name: "sqrt", min_args: 1, max_args: Some(1), func: sqrt },
+    Builtin { name: "exp", min_args: 1, max_args: Some(1), func: exp },
+    Builtin { name: "log", min_args: 1, max_args: Some(2), func: log },
+    Builtin { name: "sin", min_args: 1, max_args: Some(1), func: sin },
+    Builtin { name: "cos", min_args: 1, max_args: Some(1), func: cos },
+    Builtin { name: "tan", min_args: 1, max_args: Some(1), func: tan },
+    Builtin { name: "asin", min_args: 1, max_args: Some(1), func: asin },
+    Builtin { name: "acos", min_args: 1, max_args: Some(1), func: acos },
+    Builtin { name: "atan", min_args: 1, max_args: Some(2), func: atan },
+    Builtin { name: "exact?", min_args: 1, max_args: Some(1), func: is_exact },
+    Builtin { name: "inexact?", min_args: 1, max_args: Some(1), func: is_inexact },
+    Builtin { name: "exact", min_args: 1, max_args: Some(1), func: exact },
+    Builtin { name: "inexact", min_args: 1, max_args: Some(1), func: inexact },
+    Builtin { name: "inexact->exact", min_args: 1, max_args: Some(1), func: exact },
+    Builtin { name: "exact->inexact", min_args: 1, max_args: Some(1), func: inexact },
+    Builtin { name: "number->string", min_args: 1, max_args: Some(2), func: number_to_string },
+    Builtin { name: "string->number", min_args: 1, max_args: Some(2), func: string_to_number },
+];
+
+// Checks that the ordering holds between every pair of neighbouring
+// arguments. All the arguments have to be numbers, even after the first
+// comparison fails.
+fn comparison(name: &str, args: &[Value], holds: fn(Ordering) -> bool) -> Result<Value, RuntimeError> {
+    let numbers = args.iter().map(|arg| number(name, arg)).collect::<Result<Vec<_>, _>>()?;
+    let result = numbers.windows(2).all(|pair| pair[0].compare(pair[1]).is_some_and(holds));
+    Ok(Boolean(result))
+}
+
+fn equals(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("=", args, |ordering| ordering == Ordering::Equal)
+}
+
+fn less(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("<", args, |ordering| ordering == Ordering::Less)
+}
+
+fn greater(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison(">", args, |ordering| ordering == Ordering::Greater)
+}
+
+fn less_or_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("<=", args, |ordering| ordering != Ordering::Greater)
+}
+
+fn greater_or_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison(">=", args, |ordering| ordering != Ordering::Less)
+}
+
+fn plus(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut sum = Number::Integer(0);
+    for arg in args.iter() {
+        sum = sum.add(number("+", arg)?)
+    }
+    Ok(Number(sum))
+}
+
+fn minus(args: &[Value]) -> Result<Value, RuntimeError> {
+    let first = number("-", &args[0])?;
+    if args.len() == 1 {
+        return Ok(Number(first.neg()))
+    }
+
+    let mut difference = first.clone();
+    for arg in args[1..].iter() {
+        difference = difference.sub(number("-", arg)?)
+    }
+    Ok(Number(difference))
+}
+
+fn division(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (mut quotient, divisors) = match args.len() {
+        1 => (Number::Integer(1), args),
+        _ => (number("/", &args[0])?.clone(), &args[1..])
+    };
+
+    for arg in divisors.iter() {
+        quotient = match quotient.div(number("/", arg)?) {
+            Some(result) => result,
+            None => runtime_error!("Division by zero")
+        }
+    }
+    Ok(Number(quotient))
+}
+
+fn multiplication(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut product = Number::Integer(1);
+    for arg in args.iter() {
+        product = product.mul(number("*", arg)?)
+    }
+    Ok(Number(product))
+}
+
+fn is_number(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Number(_))))
+}
+
+fn is_integer(args: &[Value]) -> Result<Value, RuntimeError> {
+    match args[0] {
+        Number(ref x) => Ok(Boolean(x.is_integer())),
+        _ => Ok(Boolean(false))
+    }
+}
+
+fn is_zero(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(number("zero?", &args[0])?.is_zero()))
+}
+
+fn is_positive(args: &[Value]) -> Result<Value, RuntimeError> {
+    let ordering = number("positive?", &args[0])?.compare(&Number::Integer(0));
+    Ok(Boolean(ordering == Some(Ordering::Greater)))
+}
+
+fn is_negative(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(number("negative?", &args[0])?.is_negative()))
+}
+
+fn is_even(args: &[Value]) -> Result<Value, RuntimeError> {
+    let x = integral("even?", &args[0])?;
+    Ok(Boolean(x.div_rem(&Number::Integer(2), false).1.is_zero()))
+}
+
+fn is_odd(args: &[Value]) -> Result<Value, RuntimeError> {
+    let x = integral("odd?", &args[0])?;
+    Ok(Boolean(!x.div_rem(&Number::Integer(2), false).1.is_zero()))
+}
+
+// A number with an integer value, exact or not.
+fn integral<'a>(name: &str, value: &'a Value) -> Result<&'a Number, RuntimeError> {
+    match *value {
+        Number(ref x) if x.is_integer() => Ok(x),
+        _ => runtime_error!("Bad argument for '{}', expected an integer: {:?}", name, value)
+    }
+}
+
+fn integer_division(name: &str, args: &[Value], floor: bool) -> Result<(Number, Number), RuntimeError> {
+    let dividend = integral(name, &args[0])?;
+    let divisor = integral(name, &args[1])?;
+
+    if divisor.is_zero() {
+        runtime_error!("Division by zero")
+    } else {
+        Ok(dividend.div_rem(divisor, floor))
+    }
+}
+
+fn quotient(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(integer_division("quotient", args, false)?.0))
+}
+
+fn remainder(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(integer_division("remainder", args, false)?.1))
+}
+
+fn modulo(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(integer_division("modulo", args, true)?.1))
+}
+
+// There are no multiple values, so floor/, truncate/ and exact-integer-sqrt
+// return their two numbers as a two element list instead.
+fn floor_division(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (quotient, remainder) = integer_division("floor/", args, true)?;
+    Ok(Value::list(vec![Number(quotient), Number(remainder)]))
+}
+
+fn truncate_division(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (quotient, remainder) = integer_division("truncate/", args, false)?;
+    Ok(Value::list(vec![Number(quotient), Number(remainder)]))
+}
+
+// Returns the root and the rest as a list, like floor/.
+fn exact_integer_sqrt(args: &[Value]) -> Result<Value, RuntimeError> {
+    match args[0] {
+        Number(ref x) if x.is_exact() && x.is_integer() && !x.is_negative() => {
+            let (root, rest) = x.exact_integer_sqrt();
+            Ok(Value::list(vec![Number(root), Number(rest)]))
+        },
+        _ => runtime_error!(
+            "Bad argument for 'exact-integer-sqrt', expected a non-negative exact integer: {:?}",
+            args[0]
+        )
+    }
+}
+
+fn abs(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(number("abs", &args[0])?.abs()))
+}
+
+// The extreme argument by the given ordering; inexact if any argument is.
+fn extremum(name: &str, args: &[Value], wanted: Ordering) -> Result<Value, RuntimeError> {
+    let mut result = number(name, &args[0])?.clone();
+    let mut exact = result.is_exact();
+
+    for arg in args[1..].iter() {
+        let x = number(name, arg)?;
+        exact = exact && x.is_exact();
+        match x.compare(&result) {
+            Some(ordering) if ordering == wanted => result = x.clone(),
+            None => result = Number::Real(f64::NAN),
+            _ => ()
+        }
+    }
+
+    Ok(Number(if exact { result } else { result.to_inexact() }))
+}
+
+fn min(args: &[Value]) -> Result<Value, RuntimeError> {
+    extremum("min", args, Ordering::Less)
+}
+
+fn max(args: &[Value]) -> Result<Value, RuntimeError> {
+    extremum("max", args, Ordering::Greater)
+}
+
+fn gcd(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = Number::Integer(0);
+    for arg in args.iter() {
+        result = result.gcd(integral("gcd", arg)?)
+    }
+    Ok(Number(result))
+}
+
+fn lcm(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = Number::Integer(1);
+    for arg in args.iter() {
+        result = result.lcm(integral("lcm", arg)?)
+    }
+    Ok(Number(result))
+}
+
+fn floor(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(number("floor", &args[0])?.floor()))
+}
+
+fn ceiling(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(number("ceiling", &args[0])?.ceiling()))
+}
+
+fn round(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(number("round", &args[0])?.round()))
+}
+
+fn truncate(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(number("truncate", &args[0])?.truncate()))
+}
+
+fn expt(args: &[Value]) -> Result<Value, RuntimeError> {
+    match number("expt", &args[0])?.expt(number("expt", &args[1])?) {
+        Ok(result) => Ok(Number(result)),
+        Err(message) => runtime_error!("{}", message)
+    }
+}
+
+fn sqrt(args: &[Value]) -> Result<Value, RuntimeError> {
+    match number("sqrt", &args[0])?.sqrt() {
+        Some(root) => Ok(Number(root)),
+        None => runtime_error!(
+            "Bad argument for 'sqrt', expected a non-negative number: {:?}", args[0]
+        )
+    }
+}
+
+// Applies a floating point function to the single argument.
+fn real(name: &str, args: &[Value], function: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    Ok(Number(Number::Real(function(number(name, &args[0])?.to_f64()))))
+}
+
+fn exp(args: &[Value]) -> Result<Value, RuntimeError> {
+    real("exp", args, f64::exp)
+}
+
+fn log(args: &[Value]) -> Result<Value, RuntimeError> {
+    match args.get(1) {
+        Some(base) => {
+            let base = number("log", base)?.to_f64();
+            Ok(Number(Number::Real(number("log", &args[0])?.to_f64().log(base))))
+        },
+        None => real("log", args, f64::ln)
+    }
+}
+
+fn sin(args: &[Value]) -> Result<Value, RuntimeError> {
+    real("sin", args, f64::sin)
+}
+
+fn cos(args: &[Value]) -> Result<Value, RuntimeError> {
+    real("cos", args, f64::cos)
+}
+
+fn tan(args: &[Value]) -> Result<Value, RuntimeError> {
+    real("tan", args, f64::tan)
+}
+
+fn asin(args: &[Value]) -> Result<Value, RuntimeError> {
+    real("asin", args, f64::asin)
+}
+
+fn acos(args: &[Value]) -> Result<Value, RuntimeError> {
+    real("acos", args, f64::acos)
+}
+
+fn atan(args: &[Value]) -> Result<Value, RuntimeError> {
+    match args.get(1) {
+        Some(x) => {
+            let x = number("atan", x)?.to_f64();
+            Ok(Number(Number::Real(number("atan", &args[0])?.to_f64().atan2(x))))
+        },
+        None => real("atan", args, f64::atan)
+    }
+}
+
+fn is_exact(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(number("exact?", &args[0])?.is_exact()))
+}
+
+fn is_inexact(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(!number("inexact?", &args[0])?.is_exact()))
+}
+
+fn exact(args: &[Value]) -> Result<Value, RuntimeError> {
+    match number("exact", &args[0])?.to_exact() {
+        Some(result) => Ok(Number(result)),
+        None => runtime_error!("No exact representation for {:?}", args[0])
+    }
+}
+
+fn inexact(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Number(number("inexact", &args[0])?.to_inexact()))
+}
+
+// The optional radix argument of the conversion procedures.
+fn radix(name: &str, args: &[Value]) -> Result<u32, RuntimeError> {
+    match args.get(1) {
+        None => Ok(10),
+        Some(value) => match integer(name, value)? {
+            radix @ (2 | 8 | 10 | 16) => Ok(radix as u32),
+            radix => runtime_error!("Bad radix for '{}', expected 2, 8, 10 or 16: {}", name, radix)
+        }
+    }
+}
+
+fn number_to_string(args: &[Value]) -> Result<Value, RuntimeError> {
+    let radix = radix("number->string", args)?;
+    match number("number->string", &args[0])?.to_string_radix(radix) {
+        Some(result) => Ok(Value::string(result.chars().collect())),
+        None => runtime_error!("Inexact numbers can only be written in radix 10: {:?}", args[0])
+    }
+}
+
+fn string_to_number(args: &[Value]) -> Result<Value, RuntimeError> {
+    let radix = radix("string->number", args)?;
+    match Number::parse_literal(&string("string->number", &args[0])?, radix) {
+        Some(result) => Ok(Number(result)),
+        None => Ok(Boolean(false))
+    }
+}
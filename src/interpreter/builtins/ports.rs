@@ -0,0 +1,148 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::builtins::byte;
+use interpreter::builtins::bytes;
+use interpreter::builtins::integer;
+use interpreter::builtins::range;
+use interpreter::value::Builtin;
+use interpreter::value::Port;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "port?", min_args: 1, max_args: Some(1), func: is_port },
+    Builtin { name: "input-port?", min_args: 1, max_args: Some(1), func: is_input_port },
+    Builtin { name: "output-port?", min_args: 1, max_args: Some(1), func: is_output_port },
+    Builtin { name: "binary-port?", min_args: 1, max_args: Some(1), func: is_port },
+    Builtin { name: "eof-object", min_args: 0, max_args: Some(0), func: eof_object },
+    Builtin { name: "eof-object?", min_args: 1, max_args: Some(1), func: is_eof_object },
+    Builtin { name: "open-input-bytevector", min_args: 1, max_args: Some(1), func: open_input },
+    Builtin { name: "open-output-bytevector", min_args: 0, max_args: Some(0), func: open_output },
+    Builtin { name: "get-output-bytevector", min_args: 1, max_args: Some(1), func: get_output },
+    Builtin { name: "read-u8", min_args: 1, max_args: Some(1), func: read_u8 },
+    Builtin { name: "peek-u8", min_args: 1, max_args: Some(1), func: peek_u8 },
+    Builtin { name: "u8-ready?", min_args: 1, max_args: Some(1), func: is_u8_ready },
+    Builtin { name: "read-bytevector", min_args: 2, max_args: Some(2), func: read_bytevector },
+    Builtin { name: "write-u8", min_args: 2, max_args: Some(2), func: write_u8 },
+    Builtin { name: "write-bytevector", min_args: 2, max_args: Some(4), func: write_bytevector },
+];
+
+// The bytes of an input port and its position in them.
+fn input<'a>(name: &str, value: &'a Value) -> Result<(&'a [u8], &'a Cell<usize>), RuntimeError> {
+    match *value {
+        Port(ref port) => match **port {
+            Port::Input { ref bytes, ref position } => Ok((bytes, position)),
+            _ => runtime_error!("Bad argument for '{}', expected an input port: {:?}", name, value)
+        },
+        _ => runtime_error!("Bad argument for '{}', expected an input port: {:?}", name, value)
+    }
+}
+
+fn output<'a>(name: &str, value: &'a Value) -> Result<&'a RefCell<Vec<u8>>, RuntimeError> {
+    match *value {
+        Port(ref port) => match **port {
+            Port::Output(ref bytes) => Ok(bytes),
+            _ => runtime_error!("Bad argument for '{}', expected an output port: {:?}", name, value)
+        },
+        _ => runtime_error!("Bad argument for '{}', expected an output port: {:?}", name, value)
+    }
+}
+
+fn is_port(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Port(_))))
+}
+
+fn is_input_port(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(input("input-port?", &args[0]).is_ok()))
+}
+
+fn is_output_port(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(output("output-port?", &args[0]).is_ok()))
+}
+
+fn eof_object(_: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Eof)
+}
+
+fn is_eof_object(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Eof)))
+}
+
+// The port reads a copy of the bytevector, so later changes to it aren't
+// seen by the port.
+fn open_input(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = bytes("open-input-bytevector", &args[0])?.value.borrow().clone();
+    Ok(Port(Rc::new(Port::Input { bytes, position: Cell::new(0) })))
+}
+
+fn open_output(_: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Port(Rc::new(Port::Output(RefCell::new(vec![])))))
+}
+
+fn get_output(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = output("get-output-bytevector", &args[0])?;
+    Ok(Value::bytevector(bytes.borrow().clone()))
+}
+
+fn read_u8(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (bytes, position) = input("read-u8", &args[0])?;
+    match bytes.get(position.get()) {
+        Some(&byte) => {
+            position.set(position.get() + 1);
+            Ok(Number(Number::Integer(byte as i64)))
+        },
+        None => Ok(Eof)
+    }
+}
+
+fn peek_u8(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (bytes, position) = input("peek-u8", &args[0])?;
+    match bytes.get(position.get()) {
+        Some(&byte) => Ok(Number(Number::Integer(byte as i64))),
+        None => Ok(Eof)
+    }
+}
+
+// The bytes are all in memory, so reading never blocks.
+fn is_u8_ready(args: &[Value]) -> Result<Value, RuntimeError> {
+    input("u8-ready?", &args[0])?;
+    Ok(Boolean(true))
+}
+
+// Reads up to `k` bytes, or the end of file object if there are none left.
+fn read_bytevector(args: &[Value]) -> Result<Value, RuntimeError> {
+    let count = integer("read-bytevector", &args[0])?;
+    if count < 0 {
+        runtime_error!("Bad count for 'read-bytevector': {}", count)
+    }
+
+    let (bytes, position) = input("read-bytevector", &args[1])?;
+    let start = position.get();
+    if start >= bytes.len() && count > 0 {
+        return Ok(Eof)
+    }
+
+    let end = bytes.len().min(start.saturating_add(count as usize));
+    position.set(end);
+    Ok(Value::bytevector(bytes[start..end].to_vec()))
+}
+
+fn write_u8(args: &[Value]) -> Result<Value, RuntimeError> {
+    let byte = byte("write-u8", &args[0])?;
+    output("write-u8", &args[1])?.borrow_mut().push(byte);
+    Ok(empty!())
+}
+
+fn write_bytevector(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = bytes("write-bytevector", &args[0])?.value.borrow().clone();
+    let port = output("write-bytevector", &args[1])?;
+    let (start, end) = range("write-bytevector", args, 2, bytes.len())?;
+
+    port.borrow_mut().extend_from_slice(&bytes[start..end]);
+    Ok(empty!())
+}
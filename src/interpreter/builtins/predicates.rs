@@ -0,0 +1,48 @@
+use errors::RuntimeError;
+
+use interpreter::value::Builtin;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "eq?", min_args: 2, max_args: Some(2), func: is_eq },
+    Builtin { name: "eqv?", min_args: 2, max_args: Some(2), func: is_eqv },
+    Builtin { name: "equal?", min_args: 2, max_args: Some(2), func: is_equal },
+    Builtin { name: "not", min_args: 1, max_args: Some(1), func: not },
+    Builtin { name: "boolean?", min_args: 1, max_args: Some(1), func: is_boolean },
+    Builtin { name: "symbol?", min_args: 1, max_args: Some(1), func: is_symbol },
+    Builtin { name: "string?", min_args: 1, max_args: Some(1), func: is_string },
+    Builtin { name: "procedure?", min_args: 1, max_args: Some(1), func: is_procedure },
+];
+
+fn is_eq(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(args[0].is_eq(&args[1])))
+}
+
+fn is_eqv(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(args[0].is_eqv(&args[1])))
+}
+
+fn is_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(args[0].is_equal(&args[1])))
+}
+
+fn not(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(!args[0].is_true()))
+}
+
+fn is_boolean(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Boolean(_))))
+}
+
+fn is_symbol(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Symbol(_))))
+}
+
+fn is_string(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], StringValue(_))))
+}
+
+fn is_procedure(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Func(_) | CaseLambda(_) | Native(_))))
+}
@@ -0,0 +1,331 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use character;
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::builtins::character;
+use interpreter::builtins::filled;
+use interpreter::builtins::index;
+use interpreter::builtins::integer;
+use interpreter::builtins::range;
+use interpreter::builtins::string;
+use interpreter::builtins::string_chars;
+use interpreter::value::Builtin;
+use interpreter::value::Shared;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "make-string", min_args: 1, max_args: Some(2), func: make_string },
+    Builtin { name: "string", min_args: 0, max_args: None, func: string_from_chars },
+    Builtin { name: "string-length", min_args: 1, max_args: Some(1), func: string_length },
+    Builtin { name: "string-ref", min_args: 2, max_args: Some(2), func: string_ref },
+    Builtin { name: "substring", min_args: 3, max_args: Some(3), func: substring },
+    Builtin { name: "string-append", min_args: 0, max_args: None, func: string_append },
+    Builtin { name: "string-copy", min_args: 1, max_args: Some(3), func: string_copy },
+    Builtin { name: "string-set!", min_args: 3, max_args: Some(3), func: string_set },
+    Builtin { name: "string-fill!", min_args: 2, max_args: Some(4), func: string_fill },
+    Builtin { name: "string-copy!", min_args: 3, max_args: Some(5), func: string_copy_to },
+    Builtin { name: "string->list", min_args: 1, max_args: Some(3), func: string_to_list },
+    Builtin { name: "list->string", min_args: 1, max_args: Some(1), func: list_to_string },
+    Builtin { name: "string-upcase", min_args: 1, max_args: Some(1), func: upcase },
+    Builtin { name: "string-downcase", min_args: 1, max_args: Some(1), func: downcase },
+    Builtin { name: "string-foldcase", min_args: 1, max_args: Some(1), func: foldcase },
+    Builtin { name: "string=?", min_args: 1, max_args: None, func: equal },
+    Builtin { name: "string<?", min_args: 1, max_args: None, func: less },
+    Builtin { name: "string>?", min_args: 1, max_args: None, func: greater },
+    Builtin { name: "string<=?", min_args: 1, max_args: None, func: less_or_equal },
+    Builtin { name: "string>=?", min_args: 1, max_args: None, func: greater_or_equal },
+    Builtin { name: "string-ci=?", min_args: 1, max_args: None, func: equal_ci },
+    Builtin { name: "string-ci<?", min_args: 1, max_args: None, func: less_ci },
+    Builtin { name: "string-ci>?", min_args: 1, max_args: None, func: greater_ci },
+    Builtin { name: "string-ci<=?", min_args: 1, max_args: None, func: less_or_equal_ci },
+    Builtin { name: "string-ci>=?", min_args: 1, max_args: None, func: greater_or_equal_ci },
+    Builtin { name: "string-index", min_args: 2, max_args: Some(2), func: string_index },
+    Builtin { name: "string-contains", min_args: 2, max_args: Some(2), func: string_contains },
+    Builtin { name: "string-split", min_args: 2, max_args: Some(2), func: string_split },
+    Builtin { name: "string-join", min_args: 1, max_args: Some(2), func: string_join },
+    Builtin { name: "string-trim", min_args: 1, max_args: Some(1), func: trim },
+    Builtin { name: "string-trim-right", min_args: 1, max_args: Some(1), func: trim_right },
+    Builtin { name: "string-trim-both", min_args: 1, max_args: Some(1), func: trim_both },
+];
+
+// A string which can be modified; literals are constants.
+fn mutable_string(name: &str, value: &Value) -> Result<Rc<Shared<Vec<char>>>, RuntimeError> {
+    match *value {
+        StringValue(ref text) if text.mutable => Ok(text.clone()),
+        StringValue(_) => runtime_error!("Can't modify a string literal with '{}': {:?}", name, value),
+        _ => runtime_error!("Bad argument for '{}', expected a string: {:?}", name, value)
+    }
+}
+
+fn make_string(args: &[Value]) -> Result<Value, RuntimeError> {
+    let length = integer("make-string", &args[0])?;
+    if length < 0 {
+        runtime_error!("Bad length for 'make-string': {}", length)
+    }
+
+    let fill = match args.get(1) {
+        Some(value) => character("make-string", value)?,
+        None => ' '
+    };
+    Ok(Value::string(filled("make-string", length as usize, fill)?))
+}
+
+fn string_from_chars(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        result.push(character("string", arg)?)
+    }
+    Ok(Value::string(result))
+}
+
+fn string_length(args: &[Value]) -> Result<Value, RuntimeError> {
+    let length = string_chars("string-length", &args[0])?.value.borrow().len();
+    Ok(Number(Number::Integer(length as i64)))
+}
+
+fn string_ref(args: &[Value]) -> Result<Value, RuntimeError> {
+    let chars = string_chars("string-ref", &args[0])?;
+    let index = integer("string-ref", &args[1])?;
+
+    match usize::try_from(index).ok().and_then(|index| chars.value.borrow().get(index).cloned()) {
+        Some(chr) => Ok(Character(chr)),
+        None => runtime_error!("Index out of range for 'string-ref': {}", index)
+    }
+}
+
+// The characters of the string between the optional start and end given
+// from the argument at `from` on.
+fn slice(name: &str, args: &[Value], from: usize) -> Result<Vec<char>, RuntimeError> {
+    let chars = string_chars(name, &args[0])?;
+    let chars = chars.value.borrow();
+    let (start, end) = range(name, args, from, chars.len())?;
+    Ok(chars[start..end].to_vec())
+}
+
+fn substring(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(slice("substring", args, 1)?))
+}
+
+fn string_append(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = vec![];
+    for arg in args.iter() {
+        result.extend_from_slice(&string_chars("string-append", arg)?.value.borrow())
+    }
+    Ok(Value::string(result))
+}
+
+fn string_copy(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(slice("string-copy", args, 1)?))
+}
+
+fn string_set(args: &[Value]) -> Result<Value, RuntimeError> {
+    let text = mutable_string("string-set!", &args[0])?;
+    let chr = character("string-set!", &args[2])?;
+    let mut chars = text.value.borrow_mut();
+    let index = integer("string-set!", &args[1])?;
+
+    match usize::try_from(index).ok().and_then(|index| chars.get_mut(index)) {
+        Some(place) => *place = chr,
+        None => runtime_error!("Index out of range for 'string-set!': {}", index)
+    }
+
+    Ok(empty!())
+}
+
+fn string_fill(args: &[Value]) -> Result<Value, RuntimeError> {
+    let text = mutable_string("string-fill!", &args[0])?;
+    let chr = character("string-fill!", &args[1])?;
+    let mut chars = text.value.borrow_mut();
+    let (start, end) = range("string-fill!", args, 2, chars.len())?;
+
+    for place in chars[start..end].iter_mut() {
+        *place = chr
+    }
+
+    Ok(empty!())
+}
+
+// Copies the characters of `from` between the optional start and end into
+// `to`, starting at the index `at`. The strings may be the same one, so the
+// characters are taken out of `from` before `to` is changed.
+fn string_copy_to(args: &[Value]) -> Result<Value, RuntimeError> {
+    let text = mutable_string("string-copy!", &args[0])?;
+    let at = index("string-copy!", &args[1], text.value.borrow().len())?;
+    let from = slice("string-copy!", &args[2..], 1)?;
+    let mut chars = text.value.borrow_mut();
+
+    if from.len() > chars.len() - at {
+        runtime_error!("Not enough room for 'string-copy!' to copy {} characters at {}", from.len(), at)
+    }
+
+    chars[at..at + from.len()].copy_from_slice(&from);
+    Ok(empty!())
+}
+
+fn string_to_list(args: &[Value]) -> Result<Value, RuntimeError> {
+    let chars = string_chars("string->list", &args[0])?;
+    let chars = chars.value.borrow();
+    let (start, end) = range("string->list", args, 1, chars.len())?;
+    Ok(Value::list(chars[start..end].iter().map(|&chr| Character(chr)).collect()))
+}
+
+fn list_to_string(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = match args[0].list_to_vec() {
+        Some(values) => values,
+        None => runtime_error!("Bad argument for 'list->string', expected a list: {:?}", args[0])
+    };
+
+    let mut result = Vec::with_capacity(values.len());
+    for value in values.iter() {
+        result.push(character("list->string", value)?)
+    }
+    Ok(Value::string(result))
+}
+
+fn upcase(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(string("string-upcase", &args[0])?.to_uppercase().chars().collect()))
+}
+
+fn downcase(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(string("string-downcase", &args[0])?.to_lowercase().chars().collect()))
+}
+
+fn foldcase(args: &[Value]) -> Result<Value, RuntimeError> {
+    let chars = string_chars("string-foldcase", &args[0])?;
+    let folded = chars.value.borrow().iter().cloned().map(character::downcase).collect();
+    Ok(Value::string(folded))
+}
+
+// Checks that the ordering holds between every pair of neighbouring
+// arguments, comparing their case folded versions if `fold` is set.
+fn comparison(
+    name: &str, args: &[Value], fold: bool, holds: fn(Ordering) -> bool
+) -> Result<Value, RuntimeError> {
+    let mut strings = vec![];
+    for arg in args.iter() {
+        let chars = string_chars(name, arg)?.value.borrow().clone();
+        strings.push(if fold { chars.into_iter().map(character::downcase).collect() } else { chars });
+    }
+
+    Ok(Boolean(strings.windows(2).all(|pair| holds(pair[0].cmp(&pair[1])))))
+}
+
+fn equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string=?", args, false, |ordering| ordering == Ordering::Equal)
+}
+
+fn less(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string<?", args, false, |ordering| ordering == Ordering::Less)
+}
+
+fn greater(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string>?", args, false, |ordering| ordering == Ordering::Greater)
+}
+
+fn less_or_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string<=?", args, false, |ordering| ordering != Ordering::Greater)
+}
+
+fn greater_or_equal(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string>=?", args, false, |ordering| ordering != Ordering::Less)
+}
+
+fn equal_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string-ci=?", args, true, |ordering| ordering == Ordering::Equal)
+}
+
+fn less_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string-ci<?", args, true, |ordering| ordering == Ordering::Less)
+}
+
+fn greater_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string-ci>?", args, true, |ordering| ordering == Ordering::Greater)
+}
+
+fn less_or_equal_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string-ci<=?", args, true, |ordering| ordering != Ordering::Greater)
+}
+
+fn greater_or_equal_ci(args: &[Value]) -> Result<Value, RuntimeError> {
+    comparison("string-ci>=?", args, true, |ordering| ordering != Ordering::Less)
+}
+
+// The character index of the first occurrence of a character, or #f.
+fn string_index(args: &[Value]) -> Result<Value, RuntimeError> {
+    let chars = string_chars("string-index", &args[0])?;
+    let wanted = character("string-index", &args[1])?;
+
+    let position = chars.value.borrow().iter().position(|&chr| chr == wanted);
+    match position {
+        Some(index) => Ok(Number(Number::Integer(index as i64))),
+        None => Ok(Boolean(false))
+    }
+}
+
+// The character index where the second string first occurs in the first
+// one, or #f.
+fn string_contains(args: &[Value]) -> Result<Value, RuntimeError> {
+    let text = string("string-contains", &args[0])?;
+    let pattern = string("string-contains", &args[1])?;
+
+    match text.find(&pattern) {
+        Some(byte_index) => Ok(Number(Number::Integer(text[..byte_index].chars().count() as i64))),
+        None => Ok(Boolean(false))
+    }
+}
+
+// Splits the string at every occurrence of the delimiter, which is either
+// a character or a non-empty string.
+fn string_split(args: &[Value]) -> Result<Value, RuntimeError> {
+    let text = string("string-split", &args[0])?;
+    let delimiter = match args[1] {
+        Character(chr) => chr.to_string(),
+        StringValue(ref delimiter) if !delimiter.value.borrow().is_empty() => {
+            delimiter.value.borrow().iter().collect()
+        },
+        _ => runtime_error!(
+            "Bad argument for 'string-split', expected a character or a non-empty string: {:?}",
+            args[1]
+        )
+    };
+
+    let parts = text.split(delimiter.as_str()).map(|part| Value::string(part.chars().collect()));
+    Ok(Value::list(parts.collect()))
+}
+
+// Joins a list of strings, putting the delimiter, a space by default,
+// between them.
+fn string_join(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = match args[0].list_to_vec() {
+        Some(values) => values,
+        None => runtime_error!("Bad argument for 'string-join', expected a list: {:?}", args[0])
+    };
+    let delimiter = match args.get(1) {
+        Some(value) => string("string-join", value)?,
+        None => " ".to_string()
+    };
+
+    let mut strings = vec![];
+    for value in values.iter() {
+        strings.push(string("string-join", value)?)
+    }
+    Ok(Value::string(strings.join(&delimiter).chars().collect()))
+}
+
+// As in SRFI 13, string-trim removes the leading white space only.
+fn trim(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(string("string-trim", &args[0])?.trim_start().chars().collect()))
+}
+
+fn trim_right(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(string("string-trim-right", &args[0])?.trim_end().chars().collect()))
+}
+
+fn trim_both(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::string(string("string-trim-both", &args[0])?.trim().chars().collect()))
+}
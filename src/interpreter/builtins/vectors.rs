@@ -0,0 +1,181 @@
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use errors::RuntimeError;
+use number::Number;
+
+use interpreter::call;
+use interpreter::builtins::filled;
+use interpreter::builtins::index;
+use interpreter::builtins::integer;
+use interpreter::builtins::range;
+use interpreter::value::Builtin;
+use interpreter::value::Shared;
+use interpreter::value::Value;
+use interpreter::value::Value::*;
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "vector?", min_args: 1, max_args: Some(1), func: is_vector },
+    Builtin { name: "make-vector", min_args: 1, max_args: Some(2), func: make_vector },
+    Builtin { name: "vector", min_args: 0, max_args: None, func: vector },
+    Builtin { name: "vector-length", min_args: 1, max_args: Some(1), func: vector_length },
+    Builtin { name: "vector-ref", min_args: 2, max_args: Some(2), func: vector_ref },
+    Builtin { name: "vector-set!", min_args: 3, max_args: Some(3), func: vector_set },
+    Builtin { name: "vector->list", min_args: 1, max_args: Some(3), func: vector_to_list },
+    Builtin { name: "list->vector", min_args: 1, max_args: Some(1), func: list_to_vector },
+    Builtin { name: "vector-fill!", min_args: 2, max_args: Some(4), func: vector_fill },
+    Builtin { name: "vector-copy", min_args: 1, max_args: Some(3), func: vector_copy },
+    Builtin { name: "vector-copy!", min_args: 3, max_args: Some(5), func: vector_copy_to },
+    Builtin { name: "vector-append", min_args: 0, max_args: None, func: vector_append },
+    Builtin { name: "vector-map", min_args: 2, max_args: None, func: vector_map },
+    Builtin { name: "vector-for-each", min_args: 2, max_args: None, func: vector_for_each },
+];
+
+fn elements(name: &str, value: &Value) -> Result<Rc<Shared<Vec<Value>>>, RuntimeError> {
+    match *value {
+        Vector(ref values) => Ok(values.clone()),
+        _ => runtime_error!("Bad argument for '{}', expected a vector: {:?}", name, value)
+    }
+}
+
+// A vector which can be modified; literals are constants.
+fn mutable_elements(name: &str, value: &Value) -> Result<Rc<Shared<Vec<Value>>>, RuntimeError> {
+    let values = elements(name, value)?;
+    if !values.mutable {
+        runtime_error!("Can't modify a vector literal with '{}': {:?}", name, value)
+    }
+    Ok(values)
+}
+
+fn is_vector(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Boolean(matches!(args[0], Vector(_))))
+}
+
+fn make_vector(args: &[Value]) -> Result<Value, RuntimeError> {
+    let length = integer("make-vector", &args[0])?;
+    if length < 0 {
+        runtime_error!("Bad length for 'make-vector': {}", length)
+    }
+
+    let fill = args.get(1).cloned().unwrap_or(Boolean(false));
+    Ok(Value::vector(filled("make-vector", length as usize, fill)?))
+}
+
+fn vector(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::vector(args.to_vec()))
+}
+
+fn vector_length(args: &[Value]) -> Result<Value, RuntimeError> {
+    let length = elements("vector-length", &args[0])?.value.borrow().len();
+    Ok(Number(Number::Integer(length as i64)))
+}
+
+fn vector_ref(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = elements("vector-ref", &args[0])?;
+    let index = integer("vector-ref", &args[1])?;
+
+    let values = values.value.borrow();
+    match usize::try_from(index).ok().and_then(|index| values.get(index)) {
+        Some(value) => Ok(value.clone()),
+        None => runtime_error!("Index out of range for 'vector-ref': {}", index)
+    }
+}
+
+fn vector_set(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = mutable_elements("vector-set!", &args[0])?;
+    let index = integer("vector-set!", &args[1])?;
+
+    let mut values = values.value.borrow_mut();
+    match usize::try_from(index).ok().and_then(|index| values.get_mut(index)) {
+        Some(place) => *place = args[2].clone(),
+        None => runtime_error!("Index out of range for 'vector-set!': {}", index)
+    }
+    Ok(empty!())
+}
+
+fn vector_to_list(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = elements("vector->list", &args[0])?;
+    let values = values.value.borrow();
+    let (start, end) = range("vector->list", args, 1, values.len())?;
+    Ok(Value::list(values[start..end].to_vec()))
+}
+
+fn list_to_vector(args: &[Value]) -> Result<Value, RuntimeError> {
+    match args[0].list_to_vec() {
+        Some(values) => Ok(Value::vector(values)),
+        None => runtime_error!("Bad argument for 'list->vector', expected a list: {:?}", args[0])
+    }
+}
+
+fn vector_fill(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = mutable_elements("vector-fill!", &args[0])?;
+    let mut values = values.value.borrow_mut();
+    let (start, end) = range("vector-fill!", args, 2, values.len())?;
+
+    for place in values[start..end].iter_mut() {
+        *place = args[1].clone()
+    }
+    Ok(empty!())
+}
+
+fn vector_copy(args: &[Value]) -> Result<Value, RuntimeError> {
+    let values = elements("vector-copy", &args[0])?;
+    let values = values.value.borrow();
+    let (start, end) = range("vector-copy", args, 1, values.len())?;
+    Ok(Value::vector(values[start..end].to_vec()))
+}
+
+// Copies the elements of `from` between the optional start and end into
+// `to`, starting at the index `at`. The vectors may be the same one.
+fn vector_copy_to(args: &[Value]) -> Result<Value, RuntimeError> {
+    let to = mutable_elements("vector-copy!", &args[0])?;
+    let from = elements("vector-copy!", &args[2])?.value.borrow().clone();
+    let mut to = to.value.borrow_mut();
+    let at = index("vector-copy!", &args[1], to.len())?;
+    let (start, end) = range("vector-copy!", args, 3, from.len())?;
+
+    if end - start > to.len() - at {
+        runtime_error!("Not enough room for 'vector-copy!' to copy {} elements at {}", end - start, at)
+    }
+
+    to[at..at + end - start].clone_from_slice(&from[start..end]);
+    Ok(empty!())
+}
+
+fn vector_append(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = vec![];
+    for arg in args.iter() {
+        result.extend(elements("vector-append", arg)?.value.borrow().iter().cloned())
+    }
+    Ok(Value::vector(result))
+}
+
+// Calls the procedure with the elements at each index of the vectors, up to
+// the length of the shortest one. The vectors aren't borrowed during the
+// calls, so the procedure is free to modify them.
+fn each_index(
+    name: &str, args: &[Value], mut f: impl FnMut(Value)
+) -> Result<(), RuntimeError> {
+    let mut vectors = vec![];
+    for arg in args[1..].iter() {
+        vectors.push(elements(name, arg)?)
+    }
+    let length = vectors.iter().map(|values| values.value.borrow().len()).min().unwrap_or(0);
+
+    for index in 0..length {
+        let arguments = vectors.iter().map(|values| values.value.borrow()[index].clone());
+        f(call(&args[0], arguments.collect())?);
+    }
+    Ok(())
+}
+
+fn vector_map(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut result = vec![];
+    each_index("vector-map", args, |value| result.push(value))?;
+    Ok(Value::vector(result))
+}
+
+fn vector_for_each(args: &[Value]) -> Result<Value, RuntimeError> {
+    each_index("vector-for-each", args, |_| ())?;
+    Ok(empty!())
+}
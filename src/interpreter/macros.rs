@@ -10,6 +10,13 @@ macro_rules! set_var(
     )
 );
 
+#[macro_export]
+macro_rules! assign_var(
+    ($runtime:expr, $name:expr, $val:expr) => (
+        $runtime.borrow_mut().assign_var_value($name, $val)
+    )
+);
+
 #[macro_export]
 macro_rules! get_var(
     ($runtime:expr, $name:expr) => (
@@ -27,13 +34,15 @@ macro_rules! node_at(
     ($nodes:expr, $position:expr) => ($nodes.get($position).unwrap())
 );
 
+// Checks the number of nodes of a special form, the keyword included. The
+// error describes the operands it expects instead.
 #[macro_export]
 macro_rules! assert_number_of_arguments(
-    ($nodes:expr, $name:expr, $number:expr) => (
+    ($nodes:expr, $name:expr, $number:expr, $operands:expr) => (
         if $nodes.len() != $number {
             runtime_error!(
-                "The '{}' construct expects {} arguments - name and value.\n\
-                Passed : {:?}", $name, $number, $nodes
+                "The '{}' construct expects {}, got {}.\n\
+                Passed : {:?}", $name, $operands, operands($nodes), $nodes
             );
         }
     )
@@ -41,26 +50,16 @@ macro_rules! assert_number_of_arguments(
 
 #[macro_export]
 macro_rules! assert_at_least_number_of_arguments(
-    ($nodes:expr, $name:expr, $number:expr) => (
+    ($nodes:expr, $name:expr, $number:expr, $operands:expr) => (
         if $nodes.len() < $number {
             runtime_error!(
-                "The '{}' construct expects at least {} arguments - name and value.\n\
-                Passed : {:?}", $name, $number, $nodes
+                "The '{}' construct expects {}, got {}.\n\
+                Passed : {:?}", $name, $operands, operands($nodes), $nodes
             );
         }
     );
 );
 
-#[macro_export]
-macro_rules! try_or_err_to_string(
-    ($val:expr) => (
-        match $val {
-            Ok(v) => v,
-            Err(e) => return Err(e.to_string())
-        }
-    )
-);
-
 #[macro_export]
 macro_rules! test_assert_run(
     ($src:expr, $res:expr) => (
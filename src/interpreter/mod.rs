@@ -2,119 +2,434 @@
 mod value;
 #[macro_use]
 mod macros;
+mod builtins;
 pub mod runtime;
 
 use errors::RuntimeError;
+use diagnostic;
+use diagnostic::Diagnostic;
 use parser::ASTNode;
+use parser::NodeKind;
 
 use lexer::tokenize;
 use parser::parse;
 
+use std::cell::Cell;
+use std::cmp;
+use std::rc::Rc;
+
 use interpreter::value::Value;
+use interpreter::value::Lambda;
 use interpreter::value::Value::*;
 use interpreter::runtime::Runtime;
 use interpreter::runtime::RuntimeNode;
 
+// Evaluates the input, with errors reported on a single line, which is what
+// the tests compare against.
+#[cfg(test)]
 pub fn run(input: &str, runtime: &RuntimeNode) -> Result<String, String> {
-    let tokens = try_or_err_to_string!(tokenize(input));
-    let ast = try_or_err_to_string!(parse(&tokens));
-    let result = try_or_err_to_string!(eval(&ast, runtime));
+    execute(input, runtime).map_err(|diagnostic| diagnostic.to_string())
+}
+
+// Evaluates the input, with errors showing the part of it they come from.
+pub fn run_with_diagnostics(input: &str, runtime: &RuntimeNode) -> Result<String, String> {
+    execute(input, runtime).map_err(|diagnostic| diagnostic.render(input))
+}
 
-    Ok(format!("{:?}", result))
+fn execute(input: &str, runtime: &RuntimeNode) -> Result<String, Diagnostic> {
+    let tokens = tokenize(input)?;
+    let ast = parse(&tokens)?;
+    let result = eval(&ast, runtime)?;
+
+    if let Unspecified = result {
+        Ok(String::new())
+    } else {
+        Ok(format!("{:?}", result))
+    }
 }
 
-pub fn eval(ast_nodes: &Vec<ASTNode>, runtime: &RuntimeNode) -> Result<Value, RuntimeError> {
+pub fn eval(ast_nodes: &[ASTNode], runtime: &RuntimeNode) -> Result<Value, RuntimeError> {
     let mut result = empty!();
 
     for ast_node in ast_nodes.iter() {
-        result = try!(eval_ast_node(ast_node, runtime.clone()));
+        result = eval_ast_node(ast_node, runtime.clone())?;
     }
 
     Ok(result)
 }
 
+// The outcome of evaluating a form: either its final value, or an
+// expression in tail position which the caller has to evaluate next.
+// Returning the tail expression instead of evaluating it keeps tail calls
+// from growing the Rust stack.
+enum Tail {
+    Return(Value),
+    Eval(ASTNode, RuntimeNode),
+    // The tail of a form which created a scope holding procedures referring
+    // back to it, to be released once the scope isn't used any more.
+    Release(Box<Tail>, RuntimeNode),
+}
+
+// How many scopes are waiting to be released before the ones which aren't
+// used any more are looked for, so that a loop creating them doesn't keep
+// all of them alive until it ends.
+const RELEASE_BATCH: usize = 64;
+
 fn eval_ast_node(node: &ASTNode, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    match node {
-        &ASTNode::Identifier(ref v) => {
+    finish(eval_step(node, runtime)?)
+}
+
+// How deeply evaluations can be nested, e.g. by calls which aren't in tail
+// position. Each level takes up some of the Rust stack, so going deeper
+// would overflow it and abort the whole process.
+const MAX_DEPTH: usize = 10_000;
+
+// The stack needed for MAX_DEPTH nested evaluations, with room to spare even
+// in a build without optimizations.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+thread_local! {
+    // The number of evaluations in progress on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+// Puts the depth back once an evaluation is over, even if it panicked.
+struct Depth(usize);
+
+impl Drop for Depth {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.0))
+    }
+}
+
+// Counts an evaluation nested in the ones in progress.
+fn enter() -> Result<Depth, RuntimeError> {
+    let depth = DEPTH.with(Cell::get);
+    if depth >= MAX_DEPTH {
+        runtime_error!("Maximum recursion depth exceeded")
+    }
+    DEPTH.with(|current| current.set(depth + 1));
+    Ok(Depth(depth))
+}
+
+// Keeps evaluating the tail expressions until a value comes out, then
+// releases the scopes they left behind.
+fn finish(mut tail: Tail) -> Result<Value, RuntimeError> {
+    let _depth = enter()?;
+    let mut scopes = vec![];
+    let mut batch = RELEASE_BATCH;
+
+    let result = loop {
+        tail = match tail {
+            Tail::Return(val) => break Ok(val),
+            Tail::Eval(next, env) => match eval_step(&next, env) {
+                Ok(tail) => tail,
+                Err(error) => break Err(error)
+            },
+            Tail::Release(tail, scope) => {
+                scopes.push(scope);
+                if scopes.len() >= batch {
+                    release(&mut scopes);
+                    batch = cmp::max(RELEASE_BATCH, 2 * scopes.len());
+                }
+                *tail
+            }
+        }
+    };
+
+    release(&mut scopes);
+    result
+}
+
+// Releases the scopes which aren't used any more, the innermost ones first
+// as they may refer to the others, and keeps the rest.
+fn release(scopes: &mut Vec<RuntimeNode>) {
+    let mut kept = vec![];
+    while let Some(scope) = scopes.pop() {
+        if !Runtime::release(&scope) {
+            kept.push(scope)
+        }
+    }
+    kept.reverse();
+    *scopes = kept;
+}
+
+// Hands the tail of a form over to `finish` along with the scope it created,
+// releasing the scope right away if the form failed.
+fn releasing(scope: RuntimeNode, tail: Result<Tail, RuntimeError>) -> Result<Tail, RuntimeError> {
+    match tail {
+        Ok(tail) => Ok(Tail::Release(Box::new(tail), scope)),
+        Err(error) => {
+            Runtime::release(&scope);
+            Err(error)
+        }
+    }
+}
+
+// Evaluates a single step of the node. Errors coming out of it which don't
+// have a span yet get the node's one.
+fn eval_step(node: &ASTNode, runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    step(node, runtime).map_err(|error| error.at(&node.span))
+}
+
+fn step(node: &ASTNode, runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    let value = match node.kind {
+        NodeKind::Identifier(ref v) => {
             if let Some(val) = runtime.borrow().get_var_value(v) {
-                Ok(val)
+                val
+            } else if runtime.borrow().is_var_bound(v) {
+                runtime_error!("Variable used before its definition: {}", v)
             } else {
-                runtime_error!("Identifier not found! {:?}", node)
+                let names = runtime.borrow().var_names();
+                let candidates = names.iter().map(String::as_str).chain(SPECIAL_FORMS.to_vec());
+                let suggestion = diagnostic::closest(v, candidates);
+                return Err(RuntimeError {
+                    message: format!("Identifier not found! {:?}", node),
+                    span: None,
+                    help: suggestion.map(|name| format!("did you mean `{}`?", name))
+                })
             }
         },
-        &ASTNode::Integer(v) => Ok(Integer(v)),
-        &ASTNode::Boolean(v) => Ok(Boolean(v)),
-        &ASTNode::StringNode(ref v) => Ok(StringValue(v.clone())),
-        &ASTNode::List(ref vec) => {
-            if vec.len() > 0 {
-                eval_list(vec, runtime.clone())
+        NodeKind::Number(ref v) => Number(v.clone()),
+        NodeKind::Boolean(v) => Boolean(v),
+        NodeKind::Character(v) => Character(v),
+        NodeKind::StringNode(ref v) => Value::constant_string(v),
+        NodeKind::Vector(_) => quote(node)?,
+        NodeKind::Bytevector(ref bytes) => Value::constant_bytevector(bytes.to_vec()),
+        NodeKind::List(ref vec) => {
+            if vec.is_empty() {
+                Nil
             } else {
-                Ok(empty!())
+                return eval_list(vec, runtime)
             }
-        }
-    }
+        },
+        NodeKind::DottedList(..) => runtime_error!("Can't evaluate an improper list: {:?}", node)
+    };
+
+    Ok(Tail::Return(value))
 }
 
-fn eval_list(nodes: &Vec<ASTNode>, env: RuntimeNode) -> Result<Value, RuntimeError> {
-    let first = nodes.get(0).unwrap();
+// The keywords handled by eval_list, suggested along with the bound names
+// for identifiers which aren't found.
+const SPECIAL_FORMS: [&str; 20] = [
+    "define", "set!", "lambda", "case-lambda", "let", "let*", "letrec", "letrec*", "begin", "if",
+    "cond", "case", "when", "unless", "and", "or", "quote", "quasiquote", "unquote",
+    "unquote-splicing"
+];
 
-    match first {
-        &ASTNode::Identifier(ref func) => {
+fn eval_list(nodes: &[ASTNode], env: RuntimeNode) -> Result<Tail, RuntimeError> {
+    let first = node_at!(nodes, 0);
+
+    let value = match first.kind {
+        NodeKind::Identifier(ref func) => {
             match func.as_str() {
                 "define" => define(nodes, env),
                 "set!"   => set(nodes, env),
-                "lambda" => lambda(nodes),
-                "if"     => if_construct(nodes, env),
-                "and"    => and(nodes, env),
-                "or"     => or(nodes, env),
-                "+"      => plus(nodes, env),
-                "-"      => minus(nodes, env),
-                "*"      => multiplication(nodes, env),
-                "/"      => division(nodes, env),
-                "="     => equals(nodes, env),
+                "lambda" => lambda(nodes, env),
+                "case-lambda" => case_lambda(nodes, env),
+                "let"    => return let_construct(nodes, env),
+                "let*"   => return let_star(nodes, env),
+                "letrec" => return letrec(nodes, env, "letrec"),
+                "letrec*" => return letrec(nodes, env, "letrec*"),
+                "begin"  => return eval_sequence(&nodes[1..], env),
+                "if"     => return if_construct(nodes, env),
+                "cond"   => return cond(nodes, env),
+                "case"   => return case(nodes, env),
+                "when"   => return when_unless(nodes, env, true),
+                "unless" => return when_unless(nodes, env, false),
+                "and"    => return and(nodes, env),
+                "or"     => return or(nodes, env),
                 "quote"  => {
-                    assert_number_of_arguments!(nodes, "quote", 2);
+                    assert_number_of_arguments!(nodes, "quote", 2, "a datum");
                     quote(node_at!(nodes, 1))
                 },
-                "error"  => {
-                    assert_number_of_arguments!(nodes, "error", 2);
-
-                    let e = eval_ast_node(node_at!(nodes, 1), env.clone())?;
-                    runtime_error!("{}", e);
+                "quasiquote" => {
+                    assert_number_of_arguments!(nodes, "quasiquote", 2, "a template");
+                    quasiquote(node_at!(nodes, 1), 1, &env)
                 },
-                _        => func_call(func, nodes, env)
+                "unquote" | "unquote-splicing" => {
+                    runtime_error!("Bad use of '{}' outside of 'quasiquote': {:?}", func, nodes)
+                },
+                _        => return func_call(nodes, env)
             }
         },
-        _ => {
-            runtime_error!("First element in an expression must be an identifier: {:?}", first);
+        _ => return func_call(nodes, env)
+    };
+
+    Ok(Tail::Return(value?))
+}
+
+// Evaluates the body of a procedure or a let form. The variables defined
+// in the body are bound in a new scope before it runs, so internal
+// definitions behave like letrec* and can refer to each other.
+fn eval_body(body: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    let mut names = vec![];
+    for node in body.iter() {
+        definition_names(node, &mut names);
+    }
+
+    if names.is_empty() {
+        return eval_sequence(body, runtime)
+    }
+
+    let body_runtime = scope!(runtime);
+    for name in names.into_iter() {
+        if is_var_defined!(body_runtime, &name) {
+            runtime_error!("Variable already defined: {}", name)
+        }
+        body_runtime.borrow_mut().declare_var(name);
+    }
+    releasing(body_runtime.clone(), eval_sequence(body, body_runtime))
+}
+
+// Collects the names defined by a body level form, looking into `begin`
+// forms whose definitions are spliced into the body.
+fn definition_names(node: &ASTNode, names: &mut Vec<String>) {
+    if let NodeKind::List(ref list) = node.kind {
+        match list.first().map(|node| &node.kind) {
+            Some(NodeKind::Identifier(form)) if form == "define" => {
+                let mut target = list.get(1).map(|node| &node.kind);
+                while let Some(NodeKind::List(inner)) | Some(NodeKind::DottedList(inner, _)) = target {
+                    target = inner.first().map(|node| &node.kind);
+                }
+                if let Some(NodeKind::Identifier(name)) = target {
+                    names.push(name.clone())
+                }
+            },
+            Some(NodeKind::Identifier(form)) if form == "begin" => {
+                for inner in list.iter().skip(1) {
+                    definition_names(inner, names)
+                }
+            },
+            _ => ()
         }
     }
 }
 
+// Evaluates all but the last expression of a sequence and hands the last
+// one back as a tail expression.
+fn eval_sequence(body: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    match body.split_last() {
+        Some((last, init)) => {
+            eval(init, &runtime)?;
+            Ok(Tail::Eval(last.clone(), runtime))
+        },
+        None => Ok(Tail::Return(empty!()))
+    }
+}
+
 fn quote(ast_node: &ASTNode) -> Result<Value, RuntimeError> {
-    match ast_node {
-        &ASTNode::Identifier(ref v) => Ok(Symbol(v.clone())),
-        &ASTNode::Integer(v) => Ok(Integer(v)),
-        &ASTNode::Boolean(v) => Ok(Boolean(v)),
-        &ASTNode::StringNode(ref v) => Ok(StringValue(v.clone())),
-        &ASTNode::List(ref values) => {
+    match ast_node.kind {
+        NodeKind::Identifier(ref v) => Ok(Symbol(v.clone())),
+        NodeKind::Number(ref v) => Ok(Number(v.clone())),
+        NodeKind::Boolean(v) => Ok(Boolean(v)),
+        NodeKind::Character(v) => Ok(Character(v)),
+        NodeKind::StringNode(ref v) => Ok(Value::constant_string(v)),
+        NodeKind::List(ref values) => {
             let mut result = vec![];
             for val in values.iter() { result.push(quote(val)?) }
-            Ok(List(result))
+            Ok(Value::list(result))
+        },
+        NodeKind::DottedList(ref values, ref tail) => {
+            let mut result = vec![];
+            for val in values.iter() { result.push(quote(val)?) }
+            Ok(Value::list_with_tail(result, quote(tail)?))
+        },
+        NodeKind::Vector(ref values) => {
+            let mut result = vec![];
+            for val in values.iter() { result.push(quote(val)?) }
+            Ok(Value::constant_vector(result))
+        },
+        NodeKind::Bytevector(ref bytes) => Ok(Value::constant_bytevector(bytes.to_vec()))
+    }
+}
+
+// Quotes the template, evaluating the parts unquoted at the given nesting
+// level. Each nested quasiquote goes one level deeper and each unquote one
+// level back; only the unquotes which get back to level zero are evaluated.
+fn quasiquote(template: &ASTNode, level: usize, env: &RuntimeNode) -> Result<Value, RuntimeError> {
+    match template.kind {
+        NodeKind::List(ref nodes) => {
+            match unquoted(nodes) {
+                Some(("unquote", expr)) if level == 1 => eval_ast_node(expr, env.clone()),
+                Some(("unquote-splicing", _)) if level == 1 => {
+                    runtime_error!("Bad use of 'unquote-splicing' outside of a list: {:?}", template)
+                },
+                Some((form, expr)) => {
+                    let level = if form == "quasiquote" { level + 1 } else { level - 1 };
+                    let inner = quasiquote(expr, level, env)?;
+                    Ok(Value::list(vec![Symbol(form.to_string()), inner]))
+                },
+                None => Ok(Value::list(quasiquote_elements(nodes, level, env)?))
+            }
+        },
+        NodeKind::DottedList(ref nodes, ref tail) => {
+            let values = quasiquote_elements(nodes, level, env)?;
+            Ok(Value::list_with_tail(values, quasiquote(tail, level, env)?))
+        },
+        NodeKind::Vector(ref nodes) => Ok(Value::vector(quasiquote_elements(nodes, level, env)?)),
+        _ => quote(template)
+    }
+}
+
+// The elements of a list or vector template, with the values of the
+// `(unquote-splicing expr)` elements spliced in.
+fn quasiquote_elements(
+    nodes: &[ASTNode], level: usize, env: &RuntimeNode
+) -> Result<Vec<Value>, RuntimeError> {
+    let mut result = Vec::with_capacity(nodes.len());
+    for node in nodes.iter() {
+        match node.kind {
+            NodeKind::List(ref inner) if level == 1 => match unquoted(inner) {
+                Some(("unquote-splicing", expr)) => {
+                    let value = eval_ast_node(expr, env.clone())?;
+                    match value.list_to_vec() {
+                        Some(values) => result.extend(values),
+                        None => runtime_error!(
+                            "Bad argument for 'unquote-splicing', expected a list: {:?}", value
+                        )
+                    }
+                },
+                _ => result.push(quasiquote(node, level, env)?)
+            },
+            _ => result.push(quasiquote(node, level, env)?)
         }
     }
+    Ok(result)
+}
+
+// Splits `(quasiquote x)`, `(unquote x)` and `(unquote-splicing x)` into
+// the form name and `x`.
+fn unquoted(nodes: &[ASTNode]) -> Option<(&str, &ASTNode)> {
+    match nodes {
+        [ASTNode { kind: NodeKind::Identifier(ref form), .. }, ref expr] => match form.as_str() {
+            "quasiquote" => Some(("quasiquote", expr)),
+            "unquote" => Some(("unquote", expr)),
+            "unquote-splicing" => Some(("unquote-splicing", expr)),
+            _ => None
+        },
+        _ => None
+    }
 }
 
-fn define(ast_nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_number_of_arguments!(ast_nodes, "define", 3);
+fn define(ast_nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Value, RuntimeError> {
+    assert_at_least_number_of_arguments!(
+        ast_nodes, "define", 3, "a name and a value, or a name with formals and a body"
+    );
 
-    let name = match node_at!(ast_nodes, 1) {
-        &ASTNode::Identifier(ref x) => x,
+    let (name, value) = match node_at!(ast_nodes, 1).kind {
+        NodeKind::Identifier(ref x) => {
+            assert_number_of_arguments!(ast_nodes, "define", 3, "a name and a value");
+            (x.clone(), node_at!(ast_nodes, 2).clone())
+        },
+        NodeKind::List(_) | NodeKind::DottedList(..) => {
+            procedure_definition(node_at!(ast_nodes, 1), &ast_nodes[2..])?
+        },
         _ => runtime_error!("Bad variable name in 'define': {:?}", ast_nodes)
     };
 
-    if !is_var_defined!(runtime, name) {
-        let val = eval_ast_node(node_at!(ast_nodes, 2), runtime.clone())?;
+    if !runtime.borrow().is_var_assigned(&name) {
+        let val = eval_ast_node(&value, runtime.clone())?;
         set_var!(runtime, name, val);
 
         Ok(empty!())
@@ -123,17 +438,53 @@ fn define(ast_nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, Runti
     }
 }
 
-fn set(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_number_of_arguments!(nodes, "set!", 3);
+// Turns `(define (name . formals) body ...)` into the name and the lambda
+// expression it is bound to. Curried definitions like
+// `(define ((name a) b) body ...)` become nested lambdas.
+fn procedure_definition(
+    target: &ASTNode, body: &[ASTNode]
+) -> Result<(String, ASTNode), RuntimeError> {
+    let mut target = target.clone();
+    let mut body = body.to_vec();
+
+    loop {
+        let (list, rest) = match target.kind {
+            NodeKind::List(ref list) if !list.is_empty() => (list.clone(), None),
+            NodeKind::DottedList(ref list, ref rest) => (list.clone(), Some(rest.clone())),
+            _ => runtime_error!("Bad procedure name in 'define': []")
+        };
+        let span = target.span.clone();
+        let params = match rest {
+            None => ASTNode::new(NodeKind::List(Rc::new(list[1..].to_vec())), span.clone()),
+            Some(rest) if list.len() == 1 => (*rest).clone(),
+            Some(rest) => {
+                ASTNode::new(NodeKind::DottedList(Rc::new(list[1..].to_vec()), rest), span.clone())
+            }
+        };
+        let keyword = ASTNode::new(NodeKind::Identifier("lambda".to_string()), span.clone());
+        let mut lambda = vec![keyword, params];
+        lambda.append(&mut body);
+        body = vec![ASTNode::new(NodeKind::List(Rc::new(lambda)), span)];
+
+        target = match list.first().map(|node| &node.kind) {
+            Some(NodeKind::Identifier(name)) => return Ok((name.clone(), body.remove(0))),
+            Some(NodeKind::List(_)) | Some(NodeKind::DottedList(..)) => list[0].clone(),
+            _ => runtime_error!("Bad procedure name in 'define': {:?}", list)
+        };
+    }
+}
+
+fn set(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Value, RuntimeError> {
+    assert_number_of_arguments!(nodes, "set!", 3, "a name and a value");
 
-    let name = match node_at!(nodes, 1) {
-        &ASTNode::Identifier(ref x) => x,
+    let name = match node_at!(nodes, 1).kind {
+        NodeKind::Identifier(ref x) => x,
         _ => runtime_error!("Bad variable name in 'set!': {:?}", nodes)
     };
 
-    if is_var_defined!(runtime, name) {
-        let val = eval_ast_node(nodes.get(2).unwrap(), runtime.clone())?;
-        set_var!(runtime, name, val);
+    if runtime.borrow().is_var_bound(name) {
+        let val = eval_ast_node(node_at!(nodes, 2), runtime.clone())?;
+        assign_var!(runtime, name, val);
 
         Ok(empty!())
     } else {
@@ -141,162 +492,402 @@ fn set(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError
     }
 }
 
-fn lambda(nodes: &Vec<ASTNode>) -> Result<Value, RuntimeError> {
-    assert_at_least_number_of_arguments!(nodes, "lambda", 3);
+fn lambda(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Value, RuntimeError> {
+    assert_at_least_number_of_arguments!(nodes, "lambda", 3, "formals and a body");
 
-    let args = match node_at!(nodes, 1) {
-        &ASTNode::List(ref list) => {
-            list.iter().map(|arg| {
-                if let &ASTNode::Identifier(ref s) = arg {
-                    Ok(s.clone())
-                } else {
-                    runtime_error!("Bad argument in 'lambda': {:?}", arg)
-                }
-            }).map(|arg| arg.unwrap())
-        },
-        _ => runtime_error!("Bad argument list in 'lambda' definition: {:?}", nodes)
-    };
+    let (params, rest) = formals("lambda", node_at!(nodes, 1))?;
+    let body = nodes.iter().skip(2).cloned().collect();
+    Ok(Func(Rc::new(Lambda { params, rest, body, env: runtime })))
+}
 
-    let expressions = nodes.iter().skip(2).map(|e| e.clone()).collect();
-    Ok(Func(args.collect(), expressions))
+fn case_lambda(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Value, RuntimeError> {
+    let mut clauses = Vec::with_capacity(nodes.len() - 1);
+    for clause in nodes.iter().skip(1) {
+        match clause.kind {
+            NodeKind::List(ref clause) if clause.len() > 1 => {
+                let (params, rest) = formals("case-lambda", &clause[0])?;
+                let body = clause[1..].to_vec();
+                clauses.push(Lambda { params, rest, body, env: runtime.clone() });
+            },
+            _ => runtime_error!("Bad clause in 'case-lambda': {:?}", clause)
+        }
+    }
+    Ok(CaseLambda(Rc::new(clauses)))
 }
 
-fn if_construct(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_number_of_arguments!(nodes, "if", 4);
+// Parses a lambda list: `(a b)`, `(a b . rest)` or just `args`.
+fn formals(form: &str, node: &ASTNode) -> Result<(Vec<String>, Option<String>), RuntimeError> {
+    let (list, rest) = match node.kind {
+        NodeKind::Identifier(ref rest) => return Ok((vec![], Some(rest.clone()))),
+        NodeKind::List(ref list) => (list, None),
+        NodeKind::DottedList(ref list, ref rest) => match rest.kind {
+            NodeKind::Identifier(ref rest) => (list, Some(rest.clone())),
+            _ => runtime_error!("Bad argument in '{}': {:?}", form, rest)
+        },
+        _ => runtime_error!("Bad argument list in '{}' definition: {:?}", form, node)
+    };
 
-    let condition = eval_ast_node(node_at!(nodes, 1), runtime.clone())?;
-    let n = if let Boolean(false) = condition { 3 } else { 2 };
+    let mut names: Vec<String> = Vec::with_capacity(list.len());
+    for arg in list.iter() {
+        match arg.kind {
+            NodeKind::Identifier(ref s) => {
+                if names.contains(s) {
+                    runtime_error!("Duplicate argument in '{}': {}", form, s)
+                }
+                names.push(s.clone())
+            },
+            _ => runtime_error!("Bad argument in '{}': {:?}", form, arg)
+        }
+    }
 
-    eval_ast_node(node_at!(nodes, n), runtime.clone())
+    if let Some(ref rest) = rest {
+        if names.contains(rest) {
+            runtime_error!("Duplicate argument in '{}': {}", form, rest)
+        }
+    }
+    Ok((names, rest))
 }
 
-fn and(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    let mut result = Boolean(true);
+// Parses the `((name expr) ...)` binding list of the let family of forms.
+fn bindings(form: &str, node: &ASTNode) -> Result<Vec<(String, ASTNode)>, RuntimeError> {
+    let list = match node.kind {
+        NodeKind::List(ref list) => list,
+        _ => runtime_error!("Bad binding list in '{}': {:?}", form, node)
+    };
 
-    for n in nodes.iter().skip(1) {
-        let val = eval_ast_node(n, runtime.clone())?;
-        if let Boolean(false) = val {
-            return Ok(Boolean(false))
-        } else {
-            result = val
+    let mut result: Vec<(String, ASTNode)> = Vec::with_capacity(list.len());
+    for binding in list.iter() {
+        match binding.kind {
+            NodeKind::List(ref pair) if pair.len() == 2 => {
+                let name = match pair[0].kind {
+                    NodeKind::Identifier(ref name) => name.clone(),
+                    _ => runtime_error!("Bad variable name in '{}': {:?}", form, pair[0])
+                };
+                if form != "let*" && result.iter().any(|(n, _)| *n == name) {
+                    runtime_error!("Duplicate variable in '{}': {}", form, name)
+                }
+                result.push((name, pair[1].clone()));
+            },
+            _ => runtime_error!("Bad binding in '{}': {:?}", form, binding)
         }
     }
     Ok(result)
 }
 
-fn or(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    for n in nodes.iter().skip(1) {
-        let val = eval_ast_node(n, runtime.clone())?;
-        if let Boolean(false) = val { } else { return Ok(val) }
+fn let_construct(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    assert_at_least_number_of_arguments!(nodes, "let", 3, "bindings and a body");
+
+    if let NodeKind::Identifier(ref name) = nodes[1].kind {
+        return named_let(name, nodes, runtime)
+    }
+
+    let bindings = bindings("let", &nodes[1])?;
+    let let_runtime = scope!(runtime);
+    for (name, node) in bindings.into_iter() {
+        let val = eval_ast_node(&node, runtime.clone())?;
+        set_var!(let_runtime, name, val);
     }
-    Ok(Boolean(false))
+
+    eval_body(&nodes[2..], let_runtime)
 }
 
-fn equals(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_number_of_arguments!(nodes, "=", 3);
+// `(let name ((var init) ...) body ...)` binds `name` to a procedure with
+// the given body and calls it with the initial values.
+fn named_let(name: &str, nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    assert_at_least_number_of_arguments!(nodes, "let", 4, "a name, bindings and a body");
+
+    let bindings = bindings("let", &nodes[2])?;
+    let mut params = Vec::with_capacity(bindings.len());
+    let mut args = Vec::with_capacity(bindings.len());
+    for (param, node) in bindings.into_iter() {
+        args.push(eval_ast_node(&node, runtime.clone())?);
+        params.push(param);
+    }
 
-    let left = eval_ast_node(node_at!(nodes, 1), runtime.clone())?;
-    let right = eval_ast_node(node_at!(nodes, 2), runtime.clone())?;
+    let loop_runtime = scope!(runtime);
+    let body = nodes[3..].to_vec();
+    let env = loop_runtime.clone();
+    let procedure = Func(Rc::new(Lambda { params, rest: None, body, env }));
+    set_var!(loop_runtime, name.to_string(), procedure.clone());
 
-    let left_value = if let Integer(x) = left {
-        x
-    } else {
-        runtime_error!("Bad left value for '=' {:?}", nodes)
-    };
-    let right_value = if let Integer(x) = right {
-        x
-    } else {
-        runtime_error!("Bad left value for '=' {:?}", nodes)
-    };
-    Ok(Boolean(left_value == right_value))
+    releasing(loop_runtime, apply(procedure, args))
 }
 
-fn plus(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_at_least_number_of_arguments!(nodes, "+", 3);
+fn let_star(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    assert_at_least_number_of_arguments!(nodes, "let*", 3, "bindings and a body");
+
+    let mut let_runtime = runtime;
+    for (name, node) in bindings("let*", &nodes[1])?.into_iter() {
+        let val = eval_ast_node(&node, let_runtime.clone())?;
+        let_runtime = scope!(let_runtime);
+        set_var!(let_runtime, name, val);
+    }
+
+    eval_body(&nodes[2..], let_runtime)
+}
+
+// letrec evaluates all the initial values before assigning any of them,
+// letrec* assigns each one as soon as it is evaluated.
+fn letrec(nodes: &[ASTNode], runtime: RuntimeNode, form: &str) -> Result<Tail, RuntimeError> {
+    assert_at_least_number_of_arguments!(nodes, form, 3, "bindings and a body");
+
+    let bindings = bindings(form, &nodes[1])?;
+    let let_runtime = scope!(runtime);
+    for (name, _) in bindings.iter() {
+        let_runtime.borrow_mut().declare_var(name.clone());
+    }
+
+    let tail = letrec_bindings(bindings, &let_runtime, form)
+        .and_then(|()| eval_body(&nodes[2..], let_runtime.clone()));
+    releasing(let_runtime, tail)
+}
 
-    let mut sum = 0;
-    for n in nodes.iter().skip(1) {
-        if let Integer(x) = eval_ast_node(n, runtime.clone())? {
-            sum += x
+fn letrec_bindings(
+    bindings: Vec<(String, ASTNode)>, let_runtime: &RuntimeNode, form: &str
+) -> Result<(), RuntimeError> {
+    let mut values = Vec::with_capacity(bindings.len());
+    for (name, node) in bindings.into_iter() {
+        let val = eval_ast_node(&node, let_runtime.clone())?;
+        if form == "letrec*" {
+            set_var!(let_runtime, name, val);
         } else {
-            runtime_error!("Unexpected node during +: {:?}", n)
+            values.push((name, val));
         }
-    };
-    Ok(Integer(sum))
+    }
+    for (name, val) in values.into_iter() {
+        set_var!(let_runtime, name, val);
+    }
+    Ok(())
 }
 
-fn minus(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_number_of_arguments!(nodes, "-", 3);
+fn if_construct(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    if nodes.len() != 3 && nodes.len() != 4 {
+        runtime_error!(
+            "The 'if' construct expects a test, a consequent and an optional alternative, got {}.\n\
+            Passed : {:?}", operands(nodes), nodes
+        );
+    }
 
-    let left = eval_ast_node(node_at!(nodes, 1), runtime.clone())?;
-    let right = eval_ast_node(node_at!(nodes, 2), runtime.clone())?;
+    let condition = eval_ast_node(node_at!(nodes, 1), runtime.clone())?;
+    let n = if condition.is_true() { 2 } else { 3 };
 
-    let mut result = if let Integer(x) = left {
-        x
-    } else {
-        runtime_error!("Bad left value for '-' {:?}", nodes)
-    };
+    match nodes.get(n) {
+        Some(node) => Ok(Tail::Eval(node.clone(), runtime)),
+        None => Ok(Tail::Return(empty!()))
+    }
+}
 
-    result -= if let Integer(x) = right {
-        x
-    } else {
-        runtime_error!("Bad right value for '-' -: {:?}", nodes)
-    };
-    Ok(Integer(result))
+// The number of operands a special form was given, for the errors about it.
+fn operands(nodes: &[ASTNode]) -> String {
+    match nodes.len() - 1 {
+        1 => "1 operand".to_string(),
+        count => format!("{} operands", count)
+    }
 }
 
-fn division(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_number_of_arguments!(nodes, "/", 3);
+fn is_else(node: &ASTNode) -> bool {
+    matches!(node.kind, NodeKind::Identifier(ref id) if id == "else")
+}
 
-    let left = eval_ast_node(node_at!(nodes, 1), runtime.clone())?;
-    let right = eval_ast_node(node_at!(nodes, 2), runtime.clone())?;
+fn is_arrow(node: &ASTNode) -> bool {
+    matches!(node.kind, NodeKind::Identifier(ref id) if id == "=>")
+}
 
-    let mut result = if let Integer(x) = left {
-        x
+// Evaluates the expressions of a cond or case clause once it was selected.
+// `(test => receiver)` calls the receiver with the value of the test.
+fn eval_clause(
+    form: &str, clause: &[ASTNode], value: Value, runtime: RuntimeNode
+) -> Result<Tail, RuntimeError> {
+    if !clause.is_empty() && is_arrow(&clause[0]) {
+        if clause.len() != 2 {
+            runtime_error!("Bad '=>' clause in '{}': {:?}", form, clause)
+        }
+        let receiver = eval_ast_node(&clause[1], runtime)?;
+        apply(receiver, vec![value])
+    } else if clause.is_empty() {
+        Ok(Tail::Return(value))
     } else {
-        runtime_error!("Bad left value for '/' {:?}", nodes)
-    };
+        eval_sequence(clause, runtime)
+    }
+}
 
-    result /= if let Integer(x) = right {
-        x
-    } else {
-        runtime_error!("Bad right value for '/' -: {:?}", nodes)
-    };
-    Ok(Integer(result))
+fn clauses<'a>(form: &str, nodes: &'a [ASTNode]) -> Result<Vec<&'a [ASTNode]>, RuntimeError> {
+    let mut result = Vec::with_capacity(nodes.len());
+    for (i, node) in nodes.iter().enumerate() {
+        match node.kind {
+            NodeKind::List(ref clause) if !clause.is_empty() => {
+                if is_else(&clause[0]) && i != nodes.len() - 1 {
+                    runtime_error!("The 'else' clause must be the last one in '{}'", form)
+                }
+                result.push(&clause[..])
+            },
+            _ => runtime_error!("Bad clause in '{}': {:?}", form, node)
+        }
+    }
+    Ok(result)
+}
+
+fn cond(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    for clause in clauses("cond", &nodes[1..])?.into_iter() {
+        if is_else(&clause[0]) {
+            if clause.len() < 2 || is_arrow(&clause[1]) {
+                runtime_error!("Bad 'else' clause in 'cond': {:?}", clause)
+            }
+            return eval_sequence(&clause[1..], runtime)
+        }
+
+        let test = eval_ast_node(&clause[0], runtime.clone())?;
+        if test.is_true() {
+            return eval_clause("cond", &clause[1..], test, runtime)
+        }
+    }
+    Ok(Tail::Return(empty!()))
 }
 
-fn multiplication(nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    assert_at_least_number_of_arguments!(nodes, "*", 3);
+fn case(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    assert_at_least_number_of_arguments!(nodes, "case", 2, "a key and clauses");
+
+    let key = eval_ast_node(&nodes[1], runtime.clone())?;
+    for clause in clauses("case", &nodes[2..])?.into_iter() {
+        if clause.len() < 2 {
+            runtime_error!("Bad clause in 'case': {:?}", clause)
+        }
 
-    let mut product = 1;
-    for n in nodes.iter().skip(1) {
-        if let Integer(x) = eval_ast_node(n, runtime.clone())? {
-            product *= x
+        let matches = if is_else(&clause[0]) {
+            true
+        } else if let NodeKind::List(ref data) = clause[0].kind {
+            let mut found = false;
+            for datum in data.iter() {
+                if quote(datum)?.is_eqv(&key) {
+                    found = true;
+                    break
+                }
+            }
+            found
         } else {
-            runtime_error!("Unexpected node during *: {:?}", n)
+            runtime_error!("Bad clause in 'case': {:?}", clause)
+        };
+
+        if matches {
+            return eval_clause("case", &clause[1..], key, runtime)
+        }
+    }
+    Ok(Tail::Return(empty!()))
+}
+
+fn when_unless(nodes: &[ASTNode], runtime: RuntimeNode, when: bool) -> Result<Tail, RuntimeError> {
+    let form = if when { "when" } else { "unless" };
+    assert_at_least_number_of_arguments!(nodes, form, 3, "a test and a body");
+
+    let test = eval_ast_node(&nodes[1], runtime.clone())?;
+    if test.is_true() == when {
+        eval_sequence(&nodes[2..], runtime)
+    } else {
+        Ok(Tail::Return(empty!()))
+    }
+}
+
+fn and(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    let (last, init) = match nodes[1..].split_last() {
+        Some(split) => split,
+        None => return Ok(Tail::Return(Boolean(true)))
+    };
+
+    for n in init.iter() {
+        if let Boolean(false) = eval_ast_node(n, runtime.clone())? {
+            return Ok(Tail::Return(Boolean(false)))
         }
+    }
+    Ok(Tail::Eval(last.clone(), runtime))
+}
+
+fn or(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    let (last, init) = match nodes[1..].split_last() {
+        Some(split) => split,
+        None => return Ok(Tail::Return(Boolean(false)))
     };
-    Ok(Integer(product))
+
+    for n in init.iter() {
+        let val = eval_ast_node(n, runtime.clone())?;
+        if let Boolean(false) = val { } else { return Ok(Tail::Return(val)) }
+    }
+    Ok(Tail::Eval(last.clone(), runtime))
+}
+
+fn func_call(nodes: &[ASTNode], runtime: RuntimeNode) -> Result<Tail, RuntimeError> {
+    let procedure = eval_ast_node(node_at!(nodes, 0), runtime.clone())?;
+
+    let mut args = Vec::with_capacity(nodes.len() - 1);
+    for node in nodes.iter().skip(1) {
+        args.push(eval_ast_node(node, runtime.clone())?);
+    }
+
+    apply(procedure, args)
+}
+
+// Calls a procedure with the given arguments, for the builtins taking
+// procedures as arguments.
+pub fn call(procedure: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    finish(apply(procedure.clone(), args)?)
 }
 
-fn func_call(func: &String, nodes: &Vec<ASTNode>, runtime: RuntimeNode) -> Result<Value, RuntimeError> {
-    match get_var!(runtime, func) {
-        Some(Func(args, body)) => {
-            assert_number_of_arguments!(args, func, nodes.len() - 1);
+fn apply(procedure: Value, args: Vec<Value>) -> Result<Tail, RuntimeError> {
+    match procedure {
+        Func(lambda) => {
+            if !lambda.accepts(args.len()) {
+                runtime_error!(
+                    "Wrong number of arguments: expected {}, got {}",
+                    arity(lambda.min_args(), lambda.max_args()), args.len()
+                );
+            }
 
-            let func_runtime = scope!(runtime);
-            for (arg, node) in args.iter().zip(nodes.iter().skip(1)) {
-                let val = eval_ast_node(node, runtime.clone())?;
-                set_var!(func_runtime, arg, val);
+            apply_lambda(&lambda, args)
+        },
+        CaseLambda(clauses) => {
+            match clauses.iter().find(|lambda| lambda.accepts(args.len())) {
+                Some(lambda) => apply_lambda(lambda, args),
+                None => runtime_error!(
+                    "Wrong number of arguments: no 'case-lambda' clause accepts {}",
+                    args.len()
+                )
+            }
+        },
+        Native(builtin) => {
+            let max_args = builtin.max_args.unwrap_or(usize::MAX);
+            if args.len() < builtin.min_args || args.len() > max_args {
+                runtime_error!(
+                    "Wrong number of arguments to '{}': expected {}, got {}",
+                    builtin.name, arity(builtin.min_args, builtin.max_args), args.len()
+                );
             }
 
-            Ok(eval(&body, &func_runtime)?)
+            Ok(Tail::Return((builtin.func)(&args)?))
         },
-        Some(other) => runtime_error!("Undefined function call: {:?}", other),
-        None => runtime_error!("Unknown function: {}", func)
+        other => runtime_error!("Not a procedure: {:?}", other)
     }
 }
 
+fn apply_lambda(lambda: &Lambda, mut args: Vec<Value>) -> Result<Tail, RuntimeError> {
+    let func_runtime = scope!(lambda.env);
+
+    let rest = args.split_off(lambda.params.len());
+    for (param, val) in lambda.params.iter().zip(args) {
+        set_var!(func_runtime, param, val);
+    }
+    if let Some(ref name) = lambda.rest {
+        set_var!(func_runtime, name, Value::list(rest));
+    }
+
+    eval_body(&lambda.body, func_runtime)
+}
+
+fn arity(min_args: usize, max_args: Option<usize>) -> String {
+    match max_args {
+        Some(max) if max == min_args => min_args.to_string(),
+        Some(max) => format!("between {} and {}", min_args, max),
+        None => format!("at least {}", min_args)
+    }
+}
 
 #[test]
 fn test_run_simple_expressions() {
@@ -336,7 +927,7 @@ fn test_run_define() {
 fn test_run_only_one_var_definition() {
     assert_eq!(
         run("(define x 3)\n(define x 5)", &Runtime::new()).err().unwrap(),
-        "RuntimeError: Variable already defined: x"
+        "RuntimeError: Variable already defined: x (line: 2, column: 1)"
     )
 }
 
@@ -349,7 +940,7 @@ fn test_run_set() {
 fn test_run_set_undefined() {
     assert_eq!(
         run("(set! x 5)", &Runtime::new()).err().unwrap(),
-        "RuntimeError: Can't set! an undefined variable: x"
+        "RuntimeError: Can't set! an undefined variable: x (line: 1, column: 1)"
     )
 }
 
@@ -404,6 +995,1228 @@ fn test_run_syntax_error() {
 fn test_run_error() {
     assert_eq!(
         run("(error 5)", &Runtime::new()).err().unwrap(),
-        "RuntimeError: 5"
+        "RuntimeError: 5 (line: 1, column: 1)"
     )
 }
+
+#[test]
+fn test_run_closures() {
+    test_assert_run!(
+        "(define make-adder (lambda (n) (lambda (x) (+ x n))))\n\
+         (define add5 (make-adder 5))\n\
+         (define add10 (make-adder 10))\n\
+         (+ (add5 1) (add10 1))",
+        "17"
+    );
+    test_assert_run!(
+        "(define x 1)\n\
+         (define get-x (lambda () x))\n\
+         (define call (lambda (x) (get-x)))\n\
+         (call 100)",
+        "1"
+    );
+}
+
+#[test]
+fn test_run_tail_calls() {
+    test_assert_run!(
+        "(define loop (lambda (n acc) (if (= n 0) acc (loop (- n 1) (+ acc 1)))))\n\
+         (loop 10000000 0)",
+        "10000000"
+    );
+    test_assert_run!(
+        "(define count (lambda (n) (or (= n 0) (and #t (count (- n 1))))))\n\
+         (count 1000000)",
+        "#t"
+    );
+}
+
+#[test]
+fn test_run_deep_recursion() {
+    // The stack of a test thread is too small, the REPL's one is used instead.
+    let results = std::thread::Builder::new().stack_size(STACK_SIZE).spawn(|| {
+        let runtime = Runtime::new();
+        let define = "(define (f n) (if (= n 0) 0 (+ 1 (f (- n 1)))))";
+        vec![run(define, &runtime), run("(f 9000)", &runtime), run("(f 100000)", &runtime),
+             run("(f 9000)", &runtime)]
+    }).unwrap().join().unwrap();
+
+    assert_eq!(results[1], Ok("9000".to_string()));
+    assert_eq!(
+        results[2],
+        Err("RuntimeError: Maximum recursion depth exceeded (line: 1, column: 19)".to_string())
+    );
+    assert_eq!(results[3], Ok("9000".to_string()));
+}
+
+#[test]
+fn test_run_expression_in_operator_position() {
+    test_assert_run!("((lambda (x) x) 3)", "3");
+    test_assert_run!(
+        "(define make-adder (lambda (n) (lambda (x) (+ x n))))\n\
+         ((make-adder 1) 2)",
+        "3"
+    );
+    test_assert_run!(
+        "(define twice (lambda (f x) (f (f x))))\n\
+         (twice (lambda (x) (* x x)) 3)",
+        "81"
+    );
+    test_assert_run!("((if #f 1 (lambda () 5)))", "5");
+}
+
+#[test]
+fn test_run_call_non_procedure() {
+    assert_eq!(
+        run("(5 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Not a procedure: 5 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("((lambda (x) x) 1 2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Wrong number of arguments: expected 1, got 2 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_builtins_are_values() {
+    test_assert_run!("+", "#<procedure +>");
+    test_assert_run!("(define add +) (add 1 2)", "3");
+    test_assert_run!("((lambda (op) (op 2 3 4)) *)", "24");
+    test_assert_run!("((if #t + -) 5 3)", "8");
+}
+
+#[test]
+fn test_run_shadow_builtins() {
+    test_assert_run!("(define + -) (+ 5 3)", "2");
+    test_assert_run!("((lambda (=) (= 1 2)) (lambda (a b) #t))", "#t");
+    test_assert_run!("(define f (lambda (+) (+ 1 1))) (+ 1 (f -))", "1");
+}
+
+#[test]
+fn test_run_builtin_arity() {
+    assert_eq!(
+        run("(quotient 1 2 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Wrong number of arguments to 'quotient': expected 2, got 3 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(-)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Wrong number of arguments to '-': expected at least 1, got 0 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_pairs() {
+    test_assert_run!("(cons 1 2)", "'(1 . 2)");
+    test_assert_run!("(cons 1 (cons 2 3))", "'(1 2 . 3)");
+    test_assert_run!("(cons 1 (cons 2 (quote ())))", "'(1 2)");
+    test_assert_run!("(car (cons 1 2))", "1");
+    test_assert_run!("(cdr (cons 1 2))", "2");
+    test_assert_run!("(cdr (list 1 2 3))", "'(2 3)");
+    test_assert_run!("(list)", "'()");
+    test_assert_run!("(list 1 (list 2 3) (quote a))", "'(1 (2 3) a)");
+    assert_eq!(
+        run("(car (quote ()))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'car', expected a pair: '() (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_list_predicates() {
+    test_assert_run!("(null? (quote ()))", "#t");
+    test_assert_run!("(null? (list 1))", "#f");
+    test_assert_run!("(pair? (cons 1 2))", "#t");
+    test_assert_run!("(pair? (quote ()))", "#f");
+    test_assert_run!("(pair? 5)", "#f");
+}
+
+#[test]
+fn test_run_list_operations() {
+    test_assert_run!("(length (list 1 2 3))", "3");
+    test_assert_run!("(length (quote ()))", "0");
+    test_assert_run!("(append (list 1 2) (list 3) (quote ()) (list 4 5))", "'(1 2 3 4 5)");
+    test_assert_run!("(append (list 1) 2)", "'(1 . 2)");
+    test_assert_run!("(append)", "'()");
+    test_assert_run!("(reverse (list 1 2 3))", "'(3 2 1)");
+    test_assert_run!("(list-ref (list 1 2 3) 2)", "3");
+    assert_eq!(
+        run("(list-ref (list 1 2 3) 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'list-ref': 3 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(length (cons 1 2))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'length', expected a list: '(1 . 2) (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_drop_long_lists() {
+    test_assert_run!(
+        "(define (build n acc) (if (= n 0) acc (build (- n 1) (cons n acc))))\n\
+         (length (build 1000000 '()))",
+        "1000000"
+    );
+}
+
+#[test]
+fn test_run_mutate_pairs() {
+    test_assert_run!(
+        "(define p (list 1 2 3)) (define q p) (set-car! q 10) (set-cdr! (cdr q) 4) p",
+        "'(10 2 . 4)"
+    );
+}
+
+#[test]
+fn test_run_let() {
+    test_assert_run!("(let ((x 2) (y 3)) (* x y))", "6");
+    test_assert_run!("(let () 5)", "5");
+    test_assert_run!("(define x 1) (let ((x 2) (y x)) y)", "1");
+    test_assert_run!("(let ((x 1)) (let ((x 2)) x))", "2");
+    test_assert_run!("(let ((x 1)) (let ((y 2)) x))", "1");
+}
+
+#[test]
+fn test_run_let_star() {
+    test_assert_run!("(let* ((x 2) (y (* x 3))) (+ x y))", "8");
+    test_assert_run!("(let* ((x 1) (f (lambda () x)) (x 2)) (+ x (f)))", "3");
+    test_assert_run!("(let* () 4)", "4");
+}
+
+#[test]
+fn test_run_letrec() {
+    test_assert_run!(
+        "(letrec ((even? (lambda (n) (if (= n 0) #t (odd? (- n 1)))))\n\
+                  (odd? (lambda (n) (if (= n 0) #f (even? (- n 1))))))\n\
+           (even? 1001))",
+        "#f"
+    );
+    test_assert_run!("(letrec* ((x 1) (y (+ x 1))) y)", "2");
+    assert_eq!(
+        run("(letrec ((x 1) (y (+ x 1))) y)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Variable used before its definition: x (line: 1, column: 22)"
+    );
+}
+
+#[test]
+fn test_run_named_let() {
+    test_assert_run!(
+        "(let loop ((i 0) (acc (quote ())))\n\
+           (if (= i 3) acc (loop (+ i 1) (cons i acc))))",
+        "'(2 1 0)"
+    );
+    test_assert_run!(
+        "(let loop ((n 1000000)) (if (= n 0) 0 (loop (- n 1))))",
+        "0"
+    );
+    test_assert_run!(
+        "(define next (let loop ((i 0)) (lambda (n) (if (= n 0) i ((loop (+ i 1)) (- n 1))))))\n\
+         (next 3)",
+        "3"
+    );
+}
+
+#[test]
+fn test_run_named_let_releases_its_scope() {
+    let runtime = Runtime::new();
+    run(
+        "(define (g n) (let loop ((i 0)) (if (< i 1) (loop (+ i 1)) n)))\n\
+         (define (outer k)\n\
+           (if (= k 0) k (let loop ((i 0)) (if (< i 2) (loop (+ i 1)) (outer (- k 1))))))\n\
+         (define (repeat k) (if (> k 0) (begin (g k) (repeat (- k 1)))))",
+        &runtime
+    ).unwrap();
+
+    // Each scope left over would keep the top-level one alive.
+    let references = Rc::strong_count(&runtime);
+    assert_eq!(run("(repeat 10000) (outer 10000)", &runtime), Ok("0".to_string()));
+    assert_eq!(Rc::strong_count(&runtime), references);
+}
+
+#[test]
+fn test_run_malformed_let() {
+    assert_eq!(
+        run("(let ((x)) x)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad binding in 'let': List([Identifier(\"x\")]) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(let x)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: The 'let' construct expects bindings and a body, got 1 operand.\n\
+         Passed : [Identifier(\"let\"), Identifier(\"x\")] (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(let loop ((i 0)))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: The 'let' construct expects a name, bindings and a body, got 2 operands.\n\
+         Passed : [Identifier(\"let\"), Identifier(\"loop\"), List([List([Identifier(\"i\"), \
+         Number(Integer(0))])])] (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(letrec ())", &Runtime::new()).err().unwrap(),
+        "RuntimeError: The 'letrec' construct expects bindings and a body, got 1 operand.\n\
+         Passed : [Identifier(\"letrec\"), List([])] (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(let* ((1 2)) 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad variable name in 'let*': Number(Integer(1)) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(letrec ((a 1) (a 2)) a)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Duplicate variable in 'letrec': a (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(let 5 6)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad binding list in 'let': Number(Integer(5)) (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_two_armed_if() {
+    test_assert_run!("(if #t 5)", "5");
+    test_assert_run!("(if #f 5)", "");
+    test_assert_run!("(list (if #f 5))", "'(#<unspecified>)");
+    assert_eq!(
+        run("(if 1 2 3 4)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: The 'if' construct expects a test, a consequent and an optional alternative, \
+         got 4 operands.\n\
+         Passed : [Identifier(\"if\"), Number(Integer(1)), Number(Integer(2)), Number(Integer(3)), \
+         Number(Integer(4))] (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(if #t)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: The 'if' construct expects a test, a consequent and an optional alternative, \
+         got 1 operand.\n\
+         Passed : [Identifier(\"if\"), Boolean(true)] (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_cond() {
+    test_assert_run!("(cond (#f 1) ((= 1 1) 2) (else 3))", "2");
+    test_assert_run!("(cond (#f 1) (else 2 3))", "3");
+    test_assert_run!("(cond (#f 1))", "");
+    test_assert_run!("(cond ((+ 1 2)))", "3");
+    test_assert_run!("(cond ((cdr (list 1 2)) => car) (else 5))", "2");
+    test_assert_run!(
+        "(define f (lambda (n) (cond ((= n 0) 0) (else (f (- n 1))))))\n\
+         (f 1000000)",
+        "0"
+    );
+    assert_eq!(
+        run("(cond (else 1) (#t 2))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: The 'else' clause must be the last one in 'cond' (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(cond 5)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad clause in 'cond': Number(Integer(5)) (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_case() {
+    test_assert_run!("(case (* 2 3) ((2 3 5 7) (quote prime)) ((1 4 6 8 9) (quote composite)))", "'composite");
+    test_assert_run!("(case (car (quote (c d))) ((a e i o u) 1) ((w y) 2) (else 3))", "3");
+    test_assert_run!("(case (quote y) ((a e i o u) 1) ((w y) 2) (else 3))", "2");
+    test_assert_run!("(case 5 ((1) 1))", "");
+    test_assert_run!("(case 5 ((5) => (lambda (x) (* x x))))", "25");
+    test_assert_run!("(case 7 ((5) 1) (else => (lambda (x) (+ x 1))))", "8");
+}
+
+#[test]
+fn test_run_when_unless() {
+    test_assert_run!("(when (= 1 1) 1 2)", "2");
+    test_assert_run!("(when #f 1)", "");
+    test_assert_run!("(unless #f 1 2)", "2");
+    test_assert_run!("(unless #t 1)", "");
+}
+
+#[test]
+fn test_run_begin() {
+    test_assert_run!("(begin 1 2 3)", "3");
+    test_assert_run!("(begin)", "");
+    test_assert_run!("(begin (define x 1) (define y 2)) (+ x y)", "3");
+    test_assert_run!("(define x 1) (if #t (begin (set! x 5) x) 0)", "5");
+}
+
+#[test]
+fn test_run_internal_defines() {
+    test_assert_run!(
+        "(define f (lambda (n)\n\
+           (define even? (lambda (n) (if (= n 0) #t (odd? (- n 1)))))\n\
+           (define odd? (lambda (n) (if (= n 0) #f (even? (- n 1)))))\n\
+           (even? n)))\n\
+         (f 10)",
+        "#t"
+    );
+    test_assert_run!("((lambda (x) (define x 2) x) 1)", "2");
+    test_assert_run!("(let ((x 1)) (begin (define y 2)) (define z 3) (+ x y z))", "6");
+    assert_eq!(
+        run("(define x 10) ((lambda () (define y x) (define x 2) y))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Variable used before its definition: x (line: 1, column: 37)"
+    );
+    assert_eq!(
+        run("((lambda () (define a 1) (define a 2) a))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Variable already defined: a (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_internal_defines_release_their_scope() {
+    let runtime = Runtime::new();
+    run(
+        "(define (g n) (define (h) n) (h))\n\
+         (define (l n) (letrec ((f (lambda () n)) (k (lambda () (f)))) (k)))\n\
+         (define (t n) (define (h) n) (if (= n 0) (h) (t (- n 1))))\n\
+         (define (make n) (define (h) (k)) (define (k) n) h)\n\
+         (define (repeat k) (if (> k 0) (begin (g k) (l k) (repeat (- k 1)))))",
+        &runtime
+    ).unwrap();
+
+    let references = Rc::strong_count(&runtime);
+    assert_eq!(run("(repeat 10000) (t 10000)", &runtime), Ok("0".to_string()));
+    assert_eq!(Rc::strong_count(&runtime), references);
+    assert_eq!(run("((make 5))", &runtime), Ok("5".to_string()));
+}
+
+#[test]
+fn test_run_define_procedure_shorthand() {
+    test_assert_run!("(define (square x) (* x x)) (square 5)", "25");
+    test_assert_run!("(define (five) 5) (five)", "5");
+    test_assert_run!("(define ((adder n) x) (+ n x)) ((adder 3) 4)", "7");
+    test_assert_run!("(define (((f a) b) c) (list a b c)) (((f 1) 2) 3)", "'(1 2 3)");
+    test_assert_run!(
+        "(define (f n) (define (g) (h)) (define (h) n) (g)) (f 3)",
+        "3"
+    );
+    assert_eq!(
+        run("(define (5 x) x)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad procedure name in 'define': [Number(Integer(5)), Identifier(\"x\")] \
+         (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(define () 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad procedure name in 'define': [] (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_rest_parameters() {
+    test_assert_run!("((lambda args args) 1 2 3)", "'(1 2 3)");
+    test_assert_run!("((lambda args args))", "'()");
+    test_assert_run!("((lambda (a . rest) (list a rest)) 1 2 3)", "'(1 (2 3))");
+    test_assert_run!("((lambda (a b . rest) rest) 1 2)", "'()");
+    test_assert_run!("(define (f a . rest) rest) (f 1 2)", "'(2)");
+    test_assert_run!("(define (f . rest) rest) (f)", "'()");
+    test_assert_run!("(define ((f a) . rest) (list a rest)) ((f 1) 2 3)", "'(1 (2 3))");
+    assert_eq!(
+        run("((lambda (a b . rest) a) 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Wrong number of arguments: expected at least 2, got 1 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(lambda (a . b c) a)", &Runtime::new()).err().unwrap(),
+        "ParseError: Unexpected '.', it can only come before the last datum of a list \
+         (line: 1, column: 12)"
+    );
+    assert_eq!(
+        run("(lambda (a . 1) a)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument in 'lambda': Number(Integer(1)) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(lambda (a . a) a)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Duplicate argument in 'lambda': a (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(lambda (a 1) a)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument in 'lambda': Number(Integer(1)) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(lambda (a a) a)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Duplicate argument in 'lambda': a (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_case_lambda() {
+    test_assert_run!(
+        "(define range (case-lambda ((e) (range 0 e)) ((b e) (list b e)) ((b e . s) s)))\n\
+         (list (range 5) (range 1 5) (range 1 5 2))",
+        "'((0 5) (1 5) (2))"
+    );
+    assert_eq!(
+        run("((case-lambda ((a) a) ((a b c) a)) 1 2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Wrong number of arguments: no 'case-lambda' clause accepts 2 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_set_outer_binding() {
+    test_assert_run!("(define x 1) (define (inc!) (set! x (+ x 1))) (inc!) (inc!) x", "3");
+    test_assert_run!(
+        "(define (make-counter)\n\
+           (let ((n 0)) (lambda () (set! n (+ n 1)) n)))\n\
+         (define a (make-counter))\n\
+         (define b (make-counter))\n\
+         (a) (a) (b)\n\
+         (list (a) (b))",
+        "'(3 2)"
+    );
+    test_assert_run!(
+        "(define x 1) (define (f x) (set! x 10) x) (list (f 5) x)",
+        "'(10 1)"
+    );
+    test_assert_run!("(define x 1) (let ((y 2)) (set! x y)) x", "2");
+}
+
+#[test]
+fn test_run_numeric_tower() {
+    test_assert_run!("(/ 1 2)", "1/2");
+    test_assert_run!("(/ 6 3)", "2");
+    test_assert_run!("(+ 1/3 2/3)", "1");
+    test_assert_run!("(* 1.5 2)", "3.0");
+    test_assert_run!("(+ 1/2 0.25)", "0.75");
+    test_assert_run!("(/ 1 0.0)", "+inf.0");
+    test_assert_run!("(- 0.0 +inf.0)", "-inf.0");
+    test_assert_run!("(+ 1e10 1)", "10000000001.0");
+    test_assert_run!("(= 1/2 0.5)", "#t");
+    test_assert_run!("(= 1 1.0)", "#t");
+    test_assert_run!("(= +nan.0 +nan.0)", "#f");
+    assert_eq!(
+        run("(/ 5 0)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Division by zero (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_bignums() {
+    test_assert_run!(
+        "(define (fact n) (if (= n 0) 1 (* n (fact (- n 1))))) (fact 30)",
+        "265252859812191058636308480000000"
+    );
+    test_assert_run!("(+ 9223372036854775807 1)", "9223372036854775808");
+    test_assert_run!("(- -9223372036854775808 1)", "-9223372036854775809");
+    test_assert_run!("(- (+ 9223372036854775807 1) 1)", "9223372036854775807");
+    test_assert_run!("(/ 100000000000000000000 300000000000000000000)", "1/3");
+}
+
+#[test]
+fn test_run_exactness() {
+    test_assert_run!("(exact? 1/2)", "#t");
+    test_assert_run!("(exact? 0.5)", "#f");
+    test_assert_run!("(inexact? 0.5)", "#t");
+    test_assert_run!("(exact 0.5)", "1/2");
+    test_assert_run!("(exact 2.0)", "2");
+    test_assert_run!("(inexact 1/4)", "0.25");
+    test_assert_run!("(inexact 12345678901234567890)", "1.2345678901234567e19");
+    assert_eq!(
+        run("(exact +inf.0)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: No exact representation for +inf.0 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(exact? (quote a))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'exact?', expected a number: 'a (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_number_string_conversions() {
+    test_assert_run!("(+ #x10 #b11 #o10 #d10)", "37");
+    test_assert_run!("#e1.25", "5/4");
+    test_assert_run!("#i1/8", "0.125");
+    test_assert_run!("(number->string 255)", "\"255\"");
+    test_assert_run!("(number->string 255 16)", "\"ff\"");
+    test_assert_run!("(number->string -3/4 2)", "\"-11/100\"");
+    test_assert_run!("(number->string 2.5)", "\"2.5\"");
+    test_assert_run!("(string->number \"1e2\")", "100.0");
+    test_assert_run!("(string->number \"ff\" 16)", "255");
+    test_assert_run!("(string->number \"#b101\" 16)", "5");
+    test_assert_run!("(string->number \"#e0.1\")", "1/10");
+    test_assert_run!("(string->number \"12a\")", "#f");
+    assert_eq!(
+        run("(number->string 1.5 2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Inexact numbers can only be written in radix 10: 1.5 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(string->number \"10\" 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad radix for 'string->number', expected 2, 8, 10 or 16: 3 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_variadic_arithmetic() {
+    test_assert_run!("(+)", "0");
+    test_assert_run!("(*)", "1");
+    test_assert_run!("(- 5)", "-5");
+    test_assert_run!("(- 10 1 2 3)", "4");
+    test_assert_run!("(/ 4)", "1/4");
+    test_assert_run!("(/ 60 2 3)", "10");
+    test_assert_run!("(- -9223372036854775808)", "9223372036854775808");
+}
+
+#[test]
+fn test_run_comparisons() {
+    test_assert_run!("(< 1 2 3)", "#t");
+    test_assert_run!("(< 1 3 2)", "#f");
+    test_assert_run!("(> 3 2 1/2 0.25)", "#t");
+    test_assert_run!("(<= 1 1 2)", "#t");
+    test_assert_run!("(>= 2 2 3)", "#f");
+    test_assert_run!("(= 1 1 1.0)", "#t");
+    test_assert_run!("(< 1 +nan.0)", "#f");
+    assert_eq!(
+        run("(< 2 1 (quote a))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for '<', expected a number: 'a (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_integer_division() {
+    test_assert_run!("(quotient 17 5)", "3");
+    test_assert_run!("(remainder -17 5)", "-2");
+    test_assert_run!("(modulo -17 5)", "3");
+    test_assert_run!("(modulo 17 -5)", "-3");
+    test_assert_run!("(quotient 7.0 2)", "3.0");
+    test_assert_run!("(floor/ -7 2)", "'(-4 1)");
+    test_assert_run!("(truncate/ -7 2)", "'(-3 -1)");
+    test_assert_run!("(gcd 32 -36)", "4");
+    test_assert_run!("(gcd)", "0");
+    test_assert_run!("(lcm 32 -36)", "288");
+    test_assert_run!("(lcm)", "1");
+    assert_eq!(
+        run("(modulo 1 0)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Division by zero (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(quotient 1/2 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'quotient', expected an integer: 1/2 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_numeric_functions() {
+    test_assert_run!("(abs -7/2)", "7/2");
+    test_assert_run!("(min 3 1 2)", "1");
+    test_assert_run!("(max 3 4.0 2)", "4.0");
+    test_assert_run!("(min 1 2.0)", "1.0");
+    test_assert_run!("(floor -4.3)", "-5.0");
+    test_assert_run!("(ceiling 7/2)", "4");
+    test_assert_run!("(round 2.5)", "2.0");
+    test_assert_run!("(round 7/2)", "4");
+    test_assert_run!("(truncate -4.7)", "-4.0");
+    test_assert_run!("(expt 2 10)", "1024");
+    test_assert_run!("(expt 2/3 -2)", "9/4");
+    test_assert_run!("(expt 4 0.5)", "2.0");
+    test_assert_run!("(list (expt 1 100000000000000000000) (expt -1 100000000000000000001))", "'(1 -1)");
+    test_assert_run!("(exact-integer-sqrt 17)", "'(4 1)");
+    test_assert_run!("(sqrt 16)", "4");
+    test_assert_run!("(sqrt 1/4)", "1/2");
+    test_assert_run!("(sqrt 2.25)", "1.5");
+    test_assert_run!("(exp 0)", "1.0");
+    test_assert_run!("(log 1)", "0.0");
+    test_assert_run!("(log 8 2)", "3.0");
+    test_assert_run!("(sin 0)", "0.0");
+    test_assert_run!("(atan 1 1)", format!("{:?}", ::std::f64::consts::FRAC_PI_4));
+    assert_eq!(
+        run("(expt 0 -1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Division by zero (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(exact-integer-sqrt -1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'exact-integer-sqrt', expected a non-negative exact integer: -1 \
+         (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(expt 2 1000000000000)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Exact result of 'expt' is too large (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(sqrt -4)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'sqrt', expected a non-negative number: -4 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_numeric_predicates() {
+    test_assert_run!("(number? 1/2)", "#t");
+    test_assert_run!("(number? (quote a))", "#f");
+    test_assert_run!("(integer? 2.0)", "#t");
+    test_assert_run!("(integer? 1/2)", "#f");
+    test_assert_run!("(integer? \"1\")", "#f");
+    test_assert_run!("(zero? 0.0)", "#t");
+    test_assert_run!("(positive? 1/2)", "#t");
+    test_assert_run!("(negative? -0.5)", "#t");
+    test_assert_run!("(even? 10)", "#t");
+    test_assert_run!("(odd? -7)", "#t");
+    test_assert_run!("(even? 12345678901234567890)", "#t");
+    assert_eq!(
+        run("(even? 1.5)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'even?', expected an integer: 1.5 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_arithmetic_errors_keep_runtime() {
+    let runtime = &Runtime::new();
+    run("(define x 9223372036854775807)", runtime).unwrap();
+
+    assert_eq!(
+        run("(/ x 0)", runtime).err().unwrap(),
+        "RuntimeError: Division by zero (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(/ x 2 0 1)", runtime).err().unwrap(),
+        "RuntimeError: Division by zero (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(remainder x 0)", runtime).err().unwrap(),
+        "RuntimeError: Division by zero (line: 1, column: 1)"
+    );
+    assert_eq!(run("(* x x)", runtime).unwrap(), "85070591730234615847396907784232501249");
+    assert_eq!(run("(+ x 1)", runtime).unwrap(), "9223372036854775808");
+    assert_eq!(run("(/ 1.0 0.0)", runtime).unwrap(), "+inf.0");
+}
+
+#[test]
+fn test_run_characters() {
+    test_assert_run!("#\\a", "#\\a");
+    test_assert_run!("(quote #\\space)", "#\\space");
+    test_assert_run!("(list #\\x41 #\\newline #\\x7)", "'(#\\A #\\newline #\\alarm)");
+    test_assert_run!("(char? #\\a)", "#t");
+    test_assert_run!("(char? \"a\")", "#f");
+    test_assert_run!("(char->integer #\\λ)", "955");
+    test_assert_run!("(integer->char 955)", "#\\λ");
+    test_assert_run!("(char-upcase #\\λ)", "#\\Λ");
+    test_assert_run!("(char-downcase #\\A)", "#\\a");
+    test_assert_run!("(char-foldcase #\\Σ)", "#\\σ");
+    test_assert_run!("(char-alphabetic? #\\ж)", "#t");
+    test_assert_run!("(char-numeric? #\\x0663)", "#t");
+    test_assert_run!("(char-whitespace? #\\tab)", "#t");
+    test_assert_run!("(char-upper-case? #\\Ä)", "#t");
+    test_assert_run!("(char-lower-case? #\\A)", "#f");
+    test_assert_run!("(digit-value #\\7)", "7");
+    test_assert_run!("(digit-value #\\x0664)", "4");
+    test_assert_run!("(digit-value #\\a)", "#f");
+    test_assert_run!("(char<? #\\a #\\b #\\c)", "#t");
+    test_assert_run!("(char=? #\\a #\\a #\\b)", "#f");
+    test_assert_run!("(char>=? #\\b #\\b #\\a)", "#t");
+    test_assert_run!("(char-ci=? #\\a #\\A)", "#t");
+    test_assert_run!("(char-ci<? #\\a #\\B)", "#t");
+    assert_eq!(
+        run("(integer->char 55296)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'integer->char', expected a Unicode scalar value: 55296 \
+         (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(char-upcase 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'char-upcase', expected a character: 1 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_write_strings() {
+    test_assert_run!(r#""a\tb""#, r#""a\tb""#);
+    test_assert_run!(r#""say \"hi\"\n""#, r#""say \"hi\"\n""#);
+    test_assert_run!(r#""back\\slash""#, r#""back\\slash""#);
+    test_assert_run!(r#""\x1;λ""#, r#""\x1;λ""#);
+}
+
+#[test]
+fn test_run_strings() {
+    test_assert_run!("(string-length \"λx.x\")", "4");
+    test_assert_run!("(string-ref \"λx.x\" 0)", "#\\λ");
+    test_assert_run!("(substring \"hello world\" 6 11)", "\"world\"");
+    test_assert_run!("(string-append \"a\" \"bc\" \"\")", "\"abc\"");
+    test_assert_run!("(string-append)", "\"\"");
+    test_assert_run!("(string-copy \"hello\" 1)", "\"ello\"");
+    test_assert_run!("(string-copy \"hello\" 1 3)", "\"el\"");
+    test_assert_run!("(string->list \"abc\")", "'(#\\a #\\b #\\c)");
+    test_assert_run!("(string->list \"abc\" 1 2)", "'(#\\b)");
+    test_assert_run!("(list->string (list #\\a #\\space #\\b))", "\"a b\"");
+    test_assert_run!("(string #\\a #\\b)", "\"ab\"");
+    test_assert_run!("(make-string 3 #\\z)", "\"zzz\"");
+    test_assert_run!("(string-upcase \"straße\")", "\"STRASSE\"");
+    test_assert_run!("(string-downcase \"ΑΒΓ\")", "\"αβγ\"");
+    test_assert_run!("(string-foldcase \"Hello\")", "\"hello\"");
+    assert_eq!(
+        run("(string-ref \"abc\" 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'string-ref': 3 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(substring \"abc\" 2 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad range for 'substring': 2 to 1 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(string-length 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'string-length', expected a string: 1 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(make-string 100000000000000000)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Length too large for 'make-string': 100000000000000000 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_string_comparisons() {
+    test_assert_run!("(string=? \"abc\" \"abc\" \"abc\")", "#t");
+    test_assert_run!("(string<? \"abc\" \"abd\" \"b\")", "#t");
+    test_assert_run!("(string<? \"ab\" \"abc\")", "#t");
+    test_assert_run!("(string>? \"b\" \"a\" \"a\")", "#f");
+    test_assert_run!("(string<=? \"a\" \"a\" \"b\")", "#t");
+    test_assert_run!("(string>=? \"b\" \"c\")", "#f");
+    test_assert_run!("(string-ci=? \"HeLLo\" \"hello\")", "#t");
+    test_assert_run!("(string-ci<? \"apple\" \"Banana\")", "#t");
+}
+
+#[test]
+fn test_run_string_searching() {
+    test_assert_run!("(string-index \"λx.x\" #\\.)", "2");
+    test_assert_run!("(string-index \"abc\" #\\z)", "#f");
+    test_assert_run!("(string-contains \"λx.x\" \"x.x\")", "1");
+    test_assert_run!("(string-contains \"abc\" \"cd\")", "#f");
+    test_assert_run!("(string-split \"a,b,,c\" #\\,)", "'(\"a\" \"b\" \"\" \"c\")");
+    test_assert_run!("(string-split \"a::b\" \"::\")", "'(\"a\" \"b\")");
+    test_assert_run!("(string-join (list \"a\" \"b\" \"c\"))", "\"a b c\"");
+    test_assert_run!("(string-join (list \"a\" \"b\") \", \")", "\"a, b\"");
+    test_assert_run!("(string-join (list))", "\"\"");
+    test_assert_run!("(string-trim \"  a b  \")", "\"a b  \"");
+    test_assert_run!("(string-trim-right \"  a b  \")", "\"  a b\"");
+    test_assert_run!("(string-trim-both \"\\t a b \\n\")", "\"a b\"");
+    assert_eq!(
+        run("(string-split \"abc\" \"\")", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'string-split', \
+         expected a character or a non-empty string: \"\" (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_mutable_strings() {
+    test_assert_run!(
+        "(define s (make-string 3 #\\a)) (define t s) (string-set! t 1 #\\λ) s",
+        "\"aλa\""
+    );
+    test_assert_run!(
+        "(define s (string-copy \"hello\")) (string-fill! s #\\x 1 3) s",
+        "\"hxxlo\""
+    );
+    test_assert_run!(
+        "(define s (string-copy \"abcdef\")) (string-copy! s 2 s 0 3) s",
+        "\"ababcf\""
+    );
+    test_assert_run!(
+        "(define s (string-copy \"abcdef\")) (string-copy! s 0 s 2) s",
+        "\"cdefef\""
+    );
+    test_assert_run!(
+        "(define s (make-string 1000 #\\a))
+         (define (fill i) (if (< i 1000) (begin (string-set! s i #\\λ) (fill (+ i 1)))))
+         (fill 0)
+         (list (string-length s) (string-ref s 999) (string-index s #\\a))",
+        "'(1000 #\\λ #f)"
+    );
+    test_assert_run!(
+        "(define s (string-copy \"abc\")) (define t (string-copy s)) (string-set! t 0 #\\z) s",
+        "\"abc\""
+    );
+    test_assert_run!(
+        "(define s (string #\\a)) (define l (list s)) (string-set! (car l) 0 #\\b) s",
+        "\"b\""
+    );
+    assert_eq!(
+        run("(define s \"abc\") (string-set! s 0 #\\z)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Can't modify a string literal with 'string-set!': \"abc\" (line: 1, column: 18)"
+    );
+    assert_eq!(
+        run("(string-fill! (quote \"abc\") #\\z)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Can't modify a string literal with 'string-fill!': \"abc\" (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(string-set! (make-string 2) 2 #\\z)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'string-set!': 2 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(string-copy! (make-string 2) 1 \"abc\")", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Not enough room for 'string-copy!' to copy 3 characters at 1 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_vector_literals() {
+    test_assert_run!("#(1 \"two\" #\\3)", "#(1 \"two\" #\\3)");
+    test_assert_run!("#(a (b c) #(d))", "#(a (b c) #(d))");
+    test_assert_run!("(quote #(1 x))", "#(1 x)");
+    test_assert_run!("#()", "#()");
+    test_assert_run!("(vector? #(1))", "#t");
+    test_assert_run!("(vector? (list 1))", "#f");
+    assert_eq!(
+        run("(vector-set! #(1 2) 0 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Can't modify a vector literal with 'vector-set!': #(1 2) (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_vectors() {
+    test_assert_run!("(make-vector 2 (quote x))", "#(x x)");
+    test_assert_run!("(vector 1 (+ 1 1) (list 3))", "#(1 2 (3))");
+    test_assert_run!("(vector-length (make-vector 5))", "5");
+    test_assert_run!("(vector-ref #(1 2 3) 2)", "3");
+    test_assert_run!(
+        "(define v (make-vector 3 0)) (define w v) (vector-set! w 0 (quote a)) v",
+        "#(a 0 0)"
+    );
+    test_assert_run!("(vector->list #(1 2 3))", "'(1 2 3)");
+    test_assert_run!("(vector->list #(1 2 3) 1)", "'(2 3)");
+    test_assert_run!("(list->vector (list 1 2))", "#(1 2)");
+    test_assert_run!("(define v (vector 1 2 3 4)) (vector-fill! v 0 1 3) v", "#(1 0 0 4)");
+    test_assert_run!("(vector-copy #(1 2 3) 1 2)", "#(2)");
+    test_assert_run!(
+        "(define v (vector 1 2 3 4 5)) (vector-copy! v 1 v 0 3) v",
+        "#(1 1 2 3 5)"
+    );
+    test_assert_run!("(vector-append #(1) #() #(2 3))", "#(1 2 3)");
+    assert_eq!(
+        run("(vector-ref #(1 2) 2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'vector-ref': 2 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(vector-set! (vector 1) -1 0)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'vector-set!': -1 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(vector-copy #(1 2) 0 3)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'vector-copy': 3 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(vector-length (list 1))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'vector-length', expected a vector: '(1) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(make-vector 100000000000000000)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Length too large for 'make-vector': 100000000000000000 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_vector_map_for_each() {
+    test_assert_run!("(vector-map (lambda (x) (* x x)) #(1 2 3))", "#(1 4 9)");
+    test_assert_run!("(vector-map + #(1 2 3) #(10 20))", "#(11 22)");
+    test_assert_run!(
+        "(define sum 0) (vector-for-each (lambda (x) (set! sum (+ sum x))) #(1 2 3)) sum",
+        "6"
+    );
+    test_assert_run!(
+        "(define v (vector 1 2 3))\n\
+         (vector-for-each (lambda (x) (vector-set! v 0 x)) v)\n\
+         v",
+        "#(3 2 3)"
+    );
+    assert_eq!(
+        run("(vector-map (lambda (x) (car x)) #(1))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'car', expected a pair: 1 (line: 1, column: 25)"
+    );
+    assert_eq!(
+        run("(vector-map 1 #(1))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Not a procedure: 1 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_bytevectors() {
+    test_assert_run!("#u8(1 2 255)", "#u8(1 2 255)");
+    test_assert_run!("(quote #u8())", "#u8()");
+    test_assert_run!("(bytevector? #u8(1))", "#t");
+    test_assert_run!("(bytevector? #(1))", "#f");
+    test_assert_run!("(make-bytevector 3 7)", "#u8(7 7 7)");
+    test_assert_run!("(bytevector 1 2)", "#u8(1 2)");
+    test_assert_run!("(bytevector-length #u8(1 2 3))", "3");
+    test_assert_run!("(bytevector-u8-ref #u8(1 2 3) 1)", "2");
+    test_assert_run!(
+        "(define b (make-bytevector 2 0)) (define c b) (bytevector-u8-set! c 1 200) b",
+        "#u8(0 200)"
+    );
+    test_assert_run!("(bytevector-copy #u8(1 2 3) 1)", "#u8(2 3)");
+    test_assert_run!(
+        "(define b (bytevector 1 2 3 4)) (bytevector-copy! b 0 #u8(9 8) 1) b",
+        "#u8(8 2 3 4)"
+    );
+    test_assert_run!("(bytevector-append #u8(1) #u8() #u8(2 3))", "#u8(1 2 3)");
+    test_assert_run!("(string->utf8 \"λa\")", "#u8(206 187 97)");
+    test_assert_run!("(string->utf8 \"aλb\" 1 2)", "#u8(206 187)");
+    test_assert_run!("(utf8->string #u8(206 187 97))", "\"λa\"");
+    test_assert_run!("(utf8->string #u8(65 66 67) 1)", "\"BC\"");
+    assert_eq!(
+        run("(bytevector-u8-set! #u8(1) 0 2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Can't modify a bytevector literal with 'bytevector-u8-set!': #u8(1) \
+         (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(bytevector-u8-set! (make-bytevector 1) 0 256)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'bytevector-u8-set!', expected a byte: 256 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(bytevector-u8-ref #u8(1) 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Index out of range for 'bytevector-u8-ref': 1 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("(utf8->string #u8(255))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Invalid UTF-8 for 'utf8->string': #u8(255) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("#u8(1 300)", &Runtime::new()).err().unwrap(),
+        "ParseError: Bytevector elements must be integers from 0 to 255: 300 (line: 1, column: 7)"
+    );
+    assert_eq!(
+        run("(make-bytevector 100000000000000000)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Length too large for 'make-bytevector': 100000000000000000 (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_binary_ports() {
+    test_assert_run!(
+        "(define p (open-input-bytevector #u8(1 2 3)))\n\
+         (list (peek-u8 p) (read-u8 p) (read-bytevector 5 p) (read-u8 p) (read-bytevector 1 p))",
+        "'(1 1 #u8(2 3) #<eof> #<eof>)"
+    );
+    test_assert_run!(
+        "(define p (open-output-bytevector))\n\
+         (write-u8 65 p)\n\
+         (write-bytevector #u8(1 2 3 4) p 1 3)\n\
+         (get-output-bytevector p)",
+        "#u8(65 2 3)"
+    );
+    test_assert_run!("(input-port? (open-input-bytevector #u8()))", "#t");
+    test_assert_run!("(output-port? (open-input-bytevector #u8()))", "#f");
+    test_assert_run!("(binary-port? (open-output-bytevector))", "#t");
+    test_assert_run!("(eof-object? (eof-object))", "#t");
+    test_assert_run!("(u8-ready? (open-input-bytevector #u8()))", "#t");
+    assert_eq!(
+        run("(write-u8 1 (open-input-bytevector #u8()))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'write-u8', expected an output port: #<input-port> \
+         (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_run_equivalence() {
+    test_assert_run!("(eq? (quote a) (quote a))", "#t");
+    test_assert_run!("(eq? (list 1) (list 1))", "#f");
+    test_assert_run!("(let ((x (list 1))) (eq? x x))", "#t");
+    test_assert_run!("(eq? 2 2)", "#t");
+    test_assert_run!("(eq? 1.5 1.5)", "#f");
+    test_assert_run!("(eq? car car)", "#t");
+    test_assert_run!("(eqv? 1.5 1.5)", "#t");
+    test_assert_run!("(eqv? 100000000000000000000 100000000000000000000)", "#t");
+    test_assert_run!("(eqv? 2 2.0)", "#f");
+    test_assert_run!("(eqv? #\\a #\\a)", "#t");
+    test_assert_run!("(eqv? \"a\" \"a\")", "#f");
+    test_assert_run!("(eqv? (quote ()) (quote ()))", "#t");
+    test_assert_run!("(let ((f (lambda (x) x))) (eqv? f f))", "#t");
+    test_assert_run!("(eqv? (lambda (x) x) (lambda (x) x))", "#f");
+    test_assert_run!("(equal? (list 1 (vector 2 \"x\") #u8(3)) (list 1 (vector 2 \"x\") #u8(3)))", "#t");
+    test_assert_run!("(equal? (list 1 2) (list 1 2 3))", "#f");
+    test_assert_run!("(equal? (cons 1 2) (cons 1 2))", "#t");
+    test_assert_run!("(equal? \"abc\" (string #\\a #\\b #\\c))", "#t");
+    test_assert_run!("(equal? #(1 (2)) #(1 (3)))", "#f");
+    test_assert_run!("(equal? 2 2.0)", "#f");
+}
+
+#[test]
+fn test_run_equal_circular_structures() {
+    test_assert_run!(
+        "(define a (list 1 2)) (set-cdr! (cdr a) a)\n\
+         (define b (list 1 2)) (set-cdr! (cdr b) b)\n\
+         (equal? a b)",
+        "#t"
+    );
+    test_assert_run!(
+        "(define a (list 1 2)) (set-cdr! (cdr a) a)\n\
+         (define b (list 1 3)) (set-cdr! (cdr b) b)\n\
+         (equal? a b)",
+        "#f"
+    );
+    test_assert_run!(
+        "(define v (vector 1 2)) (vector-set! v 1 v)\n\
+         (define w (vector 1 2)) (vector-set! w 1 w)\n\
+         (equal? v w)",
+        "#t"
+    );
+    test_assert_run!(
+        "(define a (list 1)) (set-cdr! a a) (list (list? a) (pair? a))",
+        "'(#f #t)"
+    );
+}
+
+#[test]
+fn test_run_write_circular_structures() {
+    test_assert_run!("(define a (list 1 2)) (set-cdr! (cdr a) a) a", "'#0=(1 2 . #0#)");
+    test_assert_run!("(define v (vector 1)) (vector-set! v 0 v) v", "#0=#(#0#)");
+    test_assert_run!("(define p (list 1)) (set-car! p p) p", "'#0=(#0#)");
+    test_assert_run!(
+        "(define a (list 1 2 3)) (set-cdr! (cdr (cdr a)) (cdr a)) a",
+        "'(1 . #0=(2 3 . #0#))"
+    );
+    test_assert_run!(
+        "(define a (list 1)) (set-cdr! a a) (define v (vector a a)) (vector-set! v 0 v) v",
+        "#0=#(#0# #1=(1 . #1#))"
+    );
+    test_assert_run!("(define x (list 1)) (list x x)", "'((1) (1))");
+}
+
+#[test]
+fn test_run_type_predicates() {
+    test_assert_run!("(symbol? (quote a))", "#t");
+    test_assert_run!("(symbol? \"a\")", "#f");
+    test_assert_run!("(string? \"a\")", "#t");
+    test_assert_run!("(string? #\\a)", "#f");
+    test_assert_run!("(boolean? #f)", "#t");
+    test_assert_run!("(boolean? (quote ()))", "#f");
+    test_assert_run!("(procedure? car)", "#t");
+    test_assert_run!("(procedure? (lambda () 1))", "#t");
+    test_assert_run!("(procedure? (case-lambda ((x) x)))", "#t");
+    test_assert_run!("(procedure? (quote car))", "#f");
+    test_assert_run!("(list? (list 1 2))", "#t");
+    test_assert_run!("(list? (quote ()))", "#t");
+    test_assert_run!("(list? (cons 1 2))", "#f");
+    test_assert_run!("(vector? #(1))", "#t");
+    test_assert_run!("(char? #\\a)", "#t");
+    test_assert_run!("(not #f)", "#t");
+    test_assert_run!("(not 0)", "#f");
+    test_assert_run!("(not (quote ()))", "#f");
+}
+
+#[test]
+fn test_quote_shorthand() {
+    test_assert_run!("'a", "'a");
+    test_assert_run!("'(1 (b \"c\") #(d))", "'(1 (b \"c\") #(d))");
+    test_assert_run!("(car ''a)", "'quote");
+    test_assert_run!("'()", "'()");
+    test_assert_run!("(eq? 'a (quote a))", "#t");
+}
+
+#[test]
+fn test_quasiquote() {
+    test_assert_run!("`(1 ,(+ 1 1) ,@(list 3 4) 5)", "'(1 2 3 4 5)");
+    test_assert_run!("`(a `(b ,(c ,(+ 1 2))))", "'(a (quasiquote (b (unquote (c 3)))))");
+    test_assert_run!("`(1 `,(+ 1 ,(+ 2 3)) 4)", "'(1 (quasiquote (unquote (+ 1 5))) 4)");
+    test_assert_run!("`#(1 ,(* 2 3) ,@(vector->list #(4 9)))", "#(1 6 4 9)");
+    test_assert_run!("`(,@'() ,@(list))", "'()");
+    test_assert_run!("(let ((x 5)) `(x ,x ,'x))", "'(x 5 x)");
+    test_assert_run!("`,(+ 2 3)", "5");
+    test_assert_run!("(quasiquote (1 (unquote (+ 1 1))))", "'(1 2)");
+    test_assert_run!("(vector-set! `#(1 ,2) 0 'a)", "");
+
+    assert_eq!(
+        run("`(1 ,@2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'unquote-splicing', expected a list: 2 (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("`,@(list 1)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad use of 'unquote-splicing' outside of a list: \
+         List([Identifier(\"unquote-splicing\"), List([Identifier(\"list\"), Number(Integer(1))])]) \
+          (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run(",a", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad use of 'unquote' outside of 'quasiquote': \
+         [Identifier(\"unquote\"), Identifier(\"a\")] (line: 1, column: 1)"
+    );
+}
+
+#[test]
+fn test_dotted_pairs() {
+    test_assert_run!("'(a . b)", "'(a . b)");
+    test_assert_run!("'(1 2 . (3 4))", "'(1 2 3 4)");
+    test_assert_run!("(cdr '(1 . (2 . ())))", "'(2)");
+    test_assert_run!("(equal? '(1 2 . 3) (cons 1 (cons 2 3)))", "#t");
+    test_assert_run!("(cdr (car '((a . 1) (b . 2))))", "1");
+    test_assert_run!("'#((1 . 2))", "#((1 . 2))");
+    test_assert_run!("(let ((x 2)) `(1 . ,x))", "'(1 . 2)");
+    test_assert_run!("`(,@(list 1 2) . ,(+ 1 2))", "'(1 2 . 3)");
+    test_assert_run!("(list? '(a . b))", "#f");
+    test_assert_run!("'(a .(b))", "'(a b)");
+    test_assert_run!("'(a .'b)", "'(a quote b)");
+    test_assert_run!("'(a .\"s\")", "'(a . \"s\")");
+
+    assert_eq!(
+        run("(+ 1 . 2)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Can't evaluate an improper list: \
+         DottedList([Identifier(\"+\"), Number(Integer(1))], Number(Integer(2))) (line: 1, column: 1)"
+    );
+    assert_eq!(
+        run("'(. a)", &Runtime::new()).err().unwrap(),
+        "ParseError: Unexpected '.', it can only come before the last datum of a list \
+         (line: 1, column: 3)"
+    );
+    assert_eq!(
+        run("'(a .)", &Runtime::new()).err().unwrap(),
+        "ParseError: Expected a datum after . (line: 1, column: 5)"
+    );
+}
+
+#[test]
+fn test_comments() {
+    test_assert_run!(
+        "; Squares a number.\n\
+         (define (square x) ; the argument\n\
+           (* x x))\n\
+         #| A block comment\n\
+            #| with a nested one |#\n\
+            spanning lines. |#\n\
+         (list (square 3) #;(square 4) #; #; 1 2 '(a #;b c))",
+        "'(9 (a c))"
+    );
+    test_assert_run!("(+ 1 2) ; the sum", "3");
+    test_assert_run!("#;(undefined) 5", "5");
+    assert_eq!(
+        run("(list 1 #;)", &Runtime::new()).err().unwrap(),
+        "ParseError: Expected a datum after #; (line: 1, column: 9)"
+    );
+}
+
+#[test]
+fn test_runtime_error_spans() {
+    assert_eq!(
+        run("(+ 1\n   y)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Identifier not found! Identifier(\"y\") (line: 2, column: 4)"
+    );
+    assert_eq!(
+        run("(define (f x)\n  (car x))\n(f 5)", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Bad argument for 'car', expected a pair: 5 (line: 2, column: 3)"
+    );
+    assert_eq!(
+        run("(vector-map (lambda (x)\n (/ 1 x)) #(1 0))", &Runtime::new()).err().unwrap(),
+        "RuntimeError: Division by zero (line: 2, column: 2)"
+    );
+}
+
+#[test]
+fn test_diagnostics() {
+    let diagnose = |input: &str| run_with_diagnostics(input, &Runtime::new()).err().unwrap();
+
+    assert_eq!(
+        diagnose("(define (square x)\n  (* x x)"),
+        "ParseError: Unexpected end of input, a paren is not closed\n \
+         --> <input>:1:1\n  \
+          |\n\
+         1 | (define (square x)\n  \
+          | ^ this paren is never closed"
+    );
+    assert_eq!(
+        diagnose("(car \"abc)"),
+        "SyntaxError: String literal is not properly closed\n \
+         --> <input>:1:6\n  \
+          |\n\
+         1 | (car \"abc)\n  \
+          |      ^"
+    );
+    assert_eq!(
+        diagnose("(lenght '(1 2))"),
+        "RuntimeError: Identifier not found! Identifier(\"lenght\")\n \
+         --> <input>:1:2\n  \
+          |\n\
+         1 | (lenght '(1 2))\n  \
+          |  ^^^^^^\n  \
+          = help: did you mean `length`?"
+    );
+    assert!(diagnose("(defin x 1)").ends_with("= help: did you mean `define`?"));
+    assert!(
+        diagnose("(define (f items)\n  (car itemz))\n(f '(1))")
+            .ends_with("= help: did you mean `items`?")
+    );
+    assert!(!diagnose("(+ 1 zzz)").contains("help"));
+}
@@ -1,39 +1,112 @@
 use std::collections::HashMap;
+use std::mem;
 use std::rc::Rc;
 use std::cell::RefCell;
 
+use interpreter::builtins;
 use interpreter::value::Value;
+use interpreter::value::Value::*;
 
 macro_rules! runtime_node(
     ($($val:tt)*) => (Rc::new(RefCell::new($($val)*)))
 );
 
 pub type RuntimeNode = Rc<RefCell<Runtime>>;
+
+// Variables bound with `declare_var` have no value until they are set,
+// which is how letrec-style bindings are kept from being read too early.
 pub struct Runtime {
     parent: Option<RuntimeNode>,
-    values: HashMap<String, Value>
+    values: HashMap<String, Option<Value>>
 }
 
 impl Runtime {
+    // The builtin procedures live in the root scope; the returned top-level
+    // scope is a child of it, so user definitions can shadow them.
     pub fn new() -> RuntimeNode {
-        runtime_node!(Runtime { parent: None, values: HashMap::new() })
+        let mut root = Runtime { parent: None, values: HashMap::new() };
+        builtins::define_builtins(&mut root);
+
+        Runtime::new_scope(runtime_node!(root))
     }
 
     pub fn new_scope(parent: RuntimeNode) -> RuntimeNode {
         runtime_node!(Runtime { parent: Some(parent), values: HashMap::new() })
     }
 
+    // The procedures bound in a scope usually refer back to it, a cycle which
+    // reference counting never frees. Once nothing but those procedures and
+    // the given reference refer to the scope, its variables are cleared to
+    // break the cycle. Returns false, leaving the scope alone, while it can
+    // still be reached from anywhere else.
+    pub fn release(scope: &RuntimeNode) -> bool {
+        let cycles: usize = scope.borrow().values.values().map(|value| match *value {
+            Some(Func(ref lambda)) if Rc::strong_count(lambda) == 1 => {
+                Rc::ptr_eq(&lambda.env, scope) as usize
+            },
+            Some(CaseLambda(ref clauses)) if Rc::strong_count(clauses) == 1 => {
+                clauses.iter().filter(|lambda| Rc::ptr_eq(&lambda.env, scope)).count()
+            },
+            _ => 0
+        }).sum();
+
+        if Rc::strong_count(scope) != cycles + 1 {
+            return false
+        }
+
+        let values = mem::take(&mut scope.borrow_mut().values);
+        let parent = scope.borrow_mut().parent.take();
+        drop((values, parent));
+        true
+    }
+
     pub fn set_var_value(&mut self, key: String, value: Value) {
-        self.values.insert(key, value);
+        self.values.insert(key, Some(value));
+    }
+
+    // Assigns to the variable in the innermost scope that binds it. Returns
+    // false if no scope does.
+    pub fn assign_var_value(&mut self, key: &str, value: Value) -> bool {
+        if let Some(slot) = self.values.get_mut(key) {
+            *slot = Some(value);
+            true
+        } else if let Some(ref parent) = self.parent {
+            parent.borrow_mut().assign_var_value(key, value)
+        } else { false }
     }
 
-    pub fn is_var_defined(&self, key: &String) -> bool {
+    pub fn declare_var(&mut self, key: String) {
+        self.values.insert(key, None);
+    }
+
+    pub fn is_var_defined(&self, key: &str) -> bool {
         self.values.contains_key(key)
     }
 
-    pub fn get_var_value(&self, key: &String) -> Option<Value> {
+    pub fn is_var_assigned(&self, key: &str) -> bool {
+        matches!(self.values.get(key), Some(&Some(_)))
+    }
+
+    pub fn is_var_bound(&self, key: &str) -> bool {
+        if self.values.contains_key(key) {
+            true
+        } else if let Some(ref parent) = self.parent {
+            parent.borrow().is_var_bound(key)
+        } else { false }
+    }
+
+    // The names bound in this scope and in the enclosing ones.
+    pub fn var_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.values.keys().cloned().collect();
+        if let Some(ref parent) = self.parent {
+            names.extend(parent.borrow().var_names());
+        }
+        names
+    }
+
+    pub fn get_var_value(&self, key: &str) -> Option<Value> {
         if let Some(val) = self.values.get(key) {
-            Some(val.clone())
+            val.clone()
         } else if let Some(ref parent) = self.parent {
             parent.borrow().get_var_value(key)
         } else { None }
@@ -1,62 +1,460 @@
+use errors::RuntimeError;
+use character;
+use number::Number;
 use parser::ASTNode;
+use interpreter::runtime::RuntimeNode;
 
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
+use std::rc::Rc;
 
-#[derive(PartialEq, Clone)]
+// A user defined procedure together with the scope it was created in.
+// Arguments beyond the required `params` are collected in a list bound to
+// `rest`, if there is one.
+pub struct Lambda {
+    pub params: Vec<String>,
+    pub rest: Option<String>,
+    pub body: Vec<ASTNode>,
+    pub env: RuntimeNode,
+}
+
+impl Lambda {
+    pub fn min_args(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn max_args(&self) -> Option<usize> {
+        if self.rest.is_some() { None } else { Some(self.params.len()) }
+    }
+
+    pub fn accepts(&self, args: usize) -> bool {
+        args >= self.min_args() && self.max_args().is_none_or(|max| args <= max)
+    }
+}
+
+pub type NativeFunc = fn(&[Value]) -> Result<Value, RuntimeError>;
+
+// A procedure implemented in Rust. `max_args` is None for variadic ones.
+#[derive(Clone, Copy)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub min_args: usize,
+    pub max_args: Option<usize>,
+    pub func: NativeFunc,
+}
+
+// A mutable pair. Pairs are shared between all the values referring to
+// them, so set-car! and set-cdr! are visible through each of them.
+pub struct Cons {
+    pub car: RefCell<Value>,
+    pub cdr: RefCell<Value>,
+}
+
+// Dropping a pair drops its cdr, which would drop the next cdr and so on,
+// recursing once per pair of the list. The pairs which only this list holds
+// on to are unlinked in a loop instead.
+impl Drop for Cons {
+    fn drop(&mut self) {
+        let mut next = self.cdr.replace(Nil);
+
+        while let Pair(pair) = next {
+            next = match Rc::try_unwrap(pair) {
+                Ok(cons) => cons.cdr.replace(Nil),
+                Err(_) => break
+            };
+        }
+    }
+}
+
+// The contents of a string, a vector or a bytevector, shared between all the values
+// referring to it like a pair. Those coming from literals are constants and
+// can't be mutated. Strings are kept as characters, so that they can be
+// indexed and modified in place.
+pub struct Shared<T> {
+    pub value: RefCell<T>,
+    pub mutable: bool,
+}
+
+// An in-memory binary port, reading from a bytevector or collecting the
+// bytes written to it.
+pub enum Port {
+    Input { bytes: Vec<u8>, position: Cell<usize> },
+    Output(RefCell<Vec<u8>>),
+}
+
+#[derive(Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Value {
     Symbol(String),
-    Integer(isize),
+    Number(Number),
     Boolean(bool),
-    StringValue(String),
-    List(Vec<Value>),
-    Func(Vec<String>, Vec<ASTNode>),
+    Character(char),
+    StringValue(Rc<Shared<Vec<char>>>),
+    Nil,
+    Pair(Rc<Cons>),
+    Vector(Rc<Shared<Vec<Value>>>),
+    Bytevector(Rc<Shared<Vec<u8>>>),
+    Port(Rc<Port>),
+    Eof,
+    Unspecified,
+    Func(Rc<Lambda>),
+    CaseLambda(Rc<Vec<Lambda>>),
+    Native(Builtin),
 }
 
 use self::Value::*;
 
 #[macro_export]
-macro_rules! empty { () => (List(vec![])) }
+macro_rules! empty { () => (Unspecified) }
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self.repr())
     }
 }
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self.repr())
     }
 }
 
 impl Value {
-    fn to_string(&self) -> String {
-        match self {
-            &Symbol(_) => format!("'{}", self.display()),
-            &List(_) => format!("'{}", self.display()),
+    pub fn cons(car: Value, cdr: Value) -> Value {
+        Pair(Rc::new(Cons { car: RefCell::new(car), cdr: RefCell::new(cdr) }))
+    }
+
+    // A newly allocated, mutable string.
+    pub fn string(chars: Vec<char>) -> Value {
+        StringValue(Rc::new(Shared { value: RefCell::new(chars), mutable: true }))
+    }
+
+    // The immutable string of a literal.
+    pub fn constant_string(text: &str) -> Value {
+        StringValue(Rc::new(Shared { value: RefCell::new(text.chars().collect()), mutable: false }))
+    }
+
+    // A newly allocated, mutable vector.
+    pub fn vector(values: Vec<Value>) -> Value {
+        Vector(Rc::new(Shared { value: RefCell::new(values), mutable: true }))
+    }
+
+    // The immutable vector of a literal.
+    pub fn constant_vector(values: Vec<Value>) -> Value {
+        Vector(Rc::new(Shared { value: RefCell::new(values), mutable: false }))
+    }
+
+    // A newly allocated, mutable bytevector.
+    pub fn bytevector(bytes: Vec<u8>) -> Value {
+        Bytevector(Rc::new(Shared { value: RefCell::new(bytes), mutable: true }))
+    }
+
+    // The immutable bytevector of a literal.
+    pub fn constant_bytevector(bytes: Vec<u8>) -> Value {
+        Bytevector(Rc::new(Shared { value: RefCell::new(bytes), mutable: false }))
+    }
+
+    // Builds a proper list out of the given values.
+    pub fn list(values: Vec<Value>) -> Value {
+        Value::list_with_tail(values, Nil)
+    }
+
+    // Builds a list out of the given values, ending with `tail` instead of
+    // the empty list.
+    pub fn list_with_tail(values: Vec<Value>, tail: Value) -> Value {
+        values.into_iter().rev().fold(tail, |list, value| Value::cons(value, list))
+    }
+
+    // Whether the value is a proper list. Circular lists are not, the cycle
+    // is found by walking the list at two different speeds.
+    pub fn is_list(&self) -> bool {
+        let mut slow = self.clone();
+        let mut fast = self.clone();
+
+        loop {
+            for _ in 0..2 {
+                fast = match fast {
+                    Nil => return true,
+                    Pair(ref pair) => pair.cdr.borrow().clone(),
+                    _ => return false
+                };
+            }
+
+            slow = match slow {
+                Pair(ref pair) => pair.cdr.borrow().clone(),
+                _ => return false
+            };
+
+            if let (Pair(a), Pair(b)) = (&slow, &fast) {
+                if Rc::ptr_eq(a, b) { return false }
+            }
+        }
+    }
+
+    // Returns the elements of a proper list, or None if the value is not one.
+    pub fn list_to_vec(&self) -> Option<Vec<Value>> {
+        if !self.is_list() {
+            return None
+        }
+
+        let mut result = vec![];
+        let mut current = self.clone();
+
+        loop {
+            current = match current {
+                Nil => return Some(result),
+                Pair(ref pair) => {
+                    result.push(pair.car.borrow().clone());
+                    pair.cdr.borrow().clone()
+                },
+                _ => return None
+            }
+        }
+    }
+
+    pub fn is_true(&self) -> bool {
+        !matches!(*self, Boolean(false))
+    }
+
+    // Equivalence in the sense of eqv?: numbers, booleans and symbols are
+    // compared by value, everything else by identity.
+    pub fn is_eqv(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Symbol(a), Symbol(b)) => a == b,
+            (Number(a), Number(b)) => a.is_eqv(b),
+            (Boolean(a), Boolean(b)) => a == b,
+            (Character(a), Character(b)) => a == b,
+            (Nil, Nil) | (Unspecified, Unspecified) => true,
+            (StringValue(a), StringValue(b)) => Rc::ptr_eq(a, b),
+            (Pair(a), Pair(b)) => Rc::ptr_eq(a, b),
+            (Vector(a), Vector(b)) => Rc::ptr_eq(a, b),
+            (Bytevector(a), Bytevector(b)) => Rc::ptr_eq(a, b),
+            (Port(a), Port(b)) => Rc::ptr_eq(a, b),
+            (Eof, Eof) => true,
+            (Func(a), Func(b)) => Rc::ptr_eq(a, b),
+            (CaseLambda(a), CaseLambda(b)) => Rc::ptr_eq(a, b),
+            (Native(a), Native(b)) => a.name == b.name,
+            _ => false
+        }
+    }
+
+    // Identity in the sense of eq?. Same as eqv?, except that only fixnums
+    // are small enough for two equal numbers to be the same object.
+    pub fn is_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Number(a), Number(b)) => {
+                matches!((a, b), (Number::Integer(x), Number::Integer(y)) if x == y)
+            },
+            _ => self.is_eqv(other)
+        }
+    }
+
+    // Structural equivalence in the sense of equal?: pairs, vectors, strings
+    // and bytevectors are equal if their contents are, everything else is
+    // compared with eqv?.
+    pub fn is_equal(&self, other: &Value) -> bool {
+        equal(self, other, &mut HashSet::new())
+    }
+
+    fn repr(&self) -> String {
+        match *self {
+            Symbol(_) | Nil | Pair(_) => format!("'{}", self.display()),
             _ => self.display()
         }
     }
 
+    // The external representation of the value. Pairs and vectors which are
+    // part of a cycle get a label, so that `#0=(1 2 . #0#)` stands for a
+    // circular list, as in R7RS.
     fn display(&self) -> String {
-        match self {
-            &Symbol(ref val) => format!("{}", val),
-            &Integer(val) => format!("{}", val),
-            &Boolean(val) => format!("#{}", if val { "t" } else { "f" }),
-            &StringValue(ref val) => format!("\"{}\"", val),
-            &List(ref val) => {
+        let mut cyclic = HashSet::new();
+        find_cycles(self, &mut HashSet::new(), &mut HashSet::new(), &mut cyclic);
+        self.write(&mut Labels { cyclic, written: HashMap::new() })
+    }
+
+    // The identity of a pair or a vector, the values which can be part of a
+    // cycle.
+    fn cell_id(&self) -> Option<usize> {
+        match *self {
+            Pair(ref pair) => Some(Rc::as_ptr(pair) as usize),
+            Vector(ref values) => Some(Rc::as_ptr(values) as usize),
+            _ => None
+        }
+    }
+
+    // Writes the value, with the label of a cyclic pair or vector in front of
+    // it the first time, and in place of it after that.
+    fn write(&self, labels: &mut Labels) -> String {
+        if let Some(id) = self.cell_id().filter(|id| labels.cyclic.contains(id)) {
+            if let Some(label) = labels.written.get(&id) {
+                return format!("#{}#", label)
+            }
+            let label = labels.written.len();
+            labels.written.insert(id, label);
+            return format!("#{}={}", label, self.write_datum(labels))
+        }
+
+        self.write_datum(labels)
+    }
+
+    fn write_datum(&self, labels: &mut Labels) -> String {
+        match *self {
+            Symbol(ref val) => val.to_string(),
+            Number(ref val) => val.to_string(),
+            Boolean(val) => format!("#{}", if val { "t" } else { "f" }),
+            Character(val) => character::write(val),
+            StringValue(ref val) => write_string(&val.value.borrow()),
+            Nil => "()".to_string(),
+            Pair(ref pair) => {
                 let mut s = String::new();
-                let mut first = true;
-                for n in val.iter() {
-                    if first {
-                        first = false;
-                    } else {
-                        s.push_str(" ");
+                s.push('(');
+                s.push_str(&pair.car.borrow().write(labels));
+
+                let mut rest = pair.cdr.borrow().clone();
+                loop {
+                    rest = match rest {
+                        Nil => break,
+                        Pair(ref next) if !labels.cyclic.contains(&(Rc::as_ptr(next) as usize)) => {
+                            s.push(' ');
+                            s.push_str(&next.car.borrow().write(labels));
+                            next.cdr.borrow().clone()
+                        },
+                        ref tail => {
+                            s.push_str(" . ");
+                            s.push_str(&tail.write(labels));
+                            break
+                        }
                     }
-                    s.push_str(&n.display());
                 }
-                format!("({})", s)
-            }
-            &Func(_, _) => format!("#<procedure>")
+
+                s.push(')');
+                s
+            },
+            Vector(ref values) => {
+                let values = values.value.borrow();
+                let elements = values.iter().map(|value| value.write(labels)).collect::<Vec<_>>();
+                format!("#({})", elements.join(" "))
+            },
+            Bytevector(ref bytes) => {
+                let bytes = bytes.value.borrow();
+                let elements = bytes.iter().map(|byte| byte.to_string()).collect::<Vec<_>>();
+                format!("#u8({})", elements.join(" "))
+            },
+            Port(ref port) => match **port {
+                Port::Input { .. } => "#<input-port>".to_string(),
+                Port::Output(_) => "#<output-port>".to_string()
+            },
+            Eof => "#<eof>".to_string(),
+            Unspecified => "#<unspecified>".to_string(),
+            Func(_) | CaseLambda(_) => "#<procedure>".to_string(),
+            Native(ref builtin) => format!("#<procedure {}>", builtin.name)
+        }
+    }
+}
+
+// The pairs and vectors which are part of a cycle, along with the labels of
+// the ones written so far.
+struct Labels {
+    cyclic: HashSet<usize>,
+    written: HashMap<usize, usize>,
+}
+
+// Finds the pairs and vectors which can be reached from themselves, walking
+// the value depth first: meeting a cell which is still on the `path` being
+// walked closes a cycle. The cycles through the cells in `done` are already
+// known. Like in equal, cdrs are followed in a loop.
+fn find_cycles(
+    value: &Value, path: &mut HashSet<usize>, done: &mut HashSet<usize>, cyclic: &mut HashSet<usize>
+) {
+    let mut chain = vec![];
+    let mut current = value.clone();
+
+    while let Some(id) = current.cell_id() {
+        if path.contains(&id) {
+            cyclic.insert(id);
+            break
+        }
+        if done.contains(&id) { break }
+        path.insert(id);
+        chain.push(id);
+
+        current = match current {
+            Pair(ref pair) => {
+                find_cycles(&pair.car.borrow(), path, done, cyclic);
+                pair.cdr.borrow().clone()
+            },
+            Vector(ref values) => {
+                for value in values.value.borrow().iter() {
+                    find_cycles(value, path, done, cyclic);
+                }
+                break
+            },
+            _ => break
+        };
+    }
+
+    for id in chain {
+        path.remove(&id);
+        done.insert(id);
+    }
+}
+
+// Compares two values for equal?, remembering the pairs and vectors being
+// compared in `seen`. Reaching the same two again means the structures are
+// circular in the same way, so the comparison can stop there as equal;
+// cdrs are followed in a loop to keep long lists off the stack.
+fn equal(a: &Value, b: &Value, seen: &mut HashSet<(usize, usize)>) -> bool {
+    let (mut a, mut b) = (a.clone(), b.clone());
+
+    loop {
+        let (next_a, next_b) = match (&a, &b) {
+            (Pair(x), Pair(y)) => {
+                if Rc::ptr_eq(x, y) || !seen.insert((Rc::as_ptr(x) as usize, Rc::as_ptr(y) as usize)) {
+                    return true
+                }
+                if !equal(&x.car.borrow(), &y.car.borrow(), seen) {
+                    return false
+                }
+                (x.cdr.borrow().clone(), y.cdr.borrow().clone())
+            },
+            (Vector(x), Vector(y)) => {
+                if Rc::ptr_eq(x, y) || !seen.insert((Rc::as_ptr(x) as usize, Rc::as_ptr(y) as usize)) {
+                    return true
+                }
+                let (x, y) = (x.value.borrow(), y.value.borrow());
+                return x.len() == y.len() && x.iter().zip(y.iter()).all(|(x, y)| equal(x, y, seen))
+            },
+            (StringValue(x), StringValue(y)) => return *x.value.borrow() == *y.value.borrow(),
+            (Bytevector(x), Bytevector(y)) => return *x.value.borrow() == *y.value.borrow(),
+            _ => return a.is_eqv(&b)
+        };
+
+        a = next_a;
+        b = next_b;
+    }
+}
+
+// The external representation of a string, with the characters which can't
+// appear verbatim escaped.
+fn write_string(text: &[char]) -> String {
+    let mut result = String::from("\"");
+
+    for &chr in text.iter() {
+        match chr {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\x07' => result.push_str("\\a"),
+            '\x08' => result.push_str("\\b"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            _ if chr.is_control() => result.push_str(&format!("\\x{:x};", chr as u32)),
+            _ => result.push(chr)
         }
     }
+
+    result.push('"');
+    result
 }
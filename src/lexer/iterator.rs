@@ -1,31 +1,37 @@
 use std::iter::Peekable;
-use std::iter::Enumerate;
-use std::str::Chars;
+use std::rc::Rc;
+use std::str::CharIndices;
 
 use errors::SyntaxError;
-use lexer::token::Token;
-use lexer::token::Token::*;
+use lexer::token::TokenKind;
+use lexer::token::TokenKind::*;
+use character;
+use number::Number;
+use span::Span;
 
+// Goes through the characters of the input along with their byte offsets,
+// keeping track of the line and the column of the next character.
 pub struct LexerIterator<'a> {
-    chars: Peekable<Enumerate<Chars<'a>>>,
-    current: Option<char>,
+    input: &'a str,
+    file: Rc<String>,
+    chars: Peekable<CharIndices<'a>>,
     line: usize,
+    column: usize,
 }
 
 impl<'a> Iterator for LexerIterator<'a> {
     type Item = (usize, char);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current == Some('\x0a') {
-            self.line += 1;
-        }
-
         let result: Option<(usize, char)> = self.chars.next();
 
-        if let Some((_, ch)) = result {
-            self.current = Some(ch)
-        } else {
-            self.current = None
+        match result {
+            Some((_, '\x0a')) => {
+                self.line += 1;
+                self.column = 0;
+            },
+            Some(_) => self.column += 1,
+            None => ()
         }
 
         result
@@ -33,21 +39,56 @@ impl<'a> Iterator for LexerIterator<'a> {
 }
 
 impl<'a> LexerIterator<'a> {
-    pub fn new(input: &'a str) -> LexerIterator<'a> {
+    pub fn new(input: &'a str, file: Rc<String>) -> LexerIterator<'a> {
         LexerIterator {
-            chars: input.chars().enumerate().peekable(),
-            current: None,
-            line: 1
+            input,
+            file,
+            chars: input.char_indices().peekable(),
+            line: 1,
+            column: 0
         }
     }
 
+    // The byte offset of the next character, or the length of the input at
+    // its end.
+    pub fn offset(&mut self) -> usize {
+        let end = self.input.len();
+        self.peek().map_or(end, |&(index, _)| index)
+    }
+
+    // The line and the column of the next character.
+    pub fn location(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    // The line and the column of the character at the given byte offset.
+    pub fn location_of(&self, index: usize) -> (usize, usize) {
+        let before = &self.input[..index];
+        let line_start = before.rfind('\x0a').map_or(0, |newline| newline + 1);
+        (before.matches('\x0a').count() + 1, before[line_start..].chars().count())
+    }
+
+    // The span of the character at the given byte offset.
+    pub fn span_at(&self, index: usize) -> Span {
+        let (line, column) = self.location_of(index);
+        let end = self.input[index..].chars().next().map_or(index, |chr| index + chr.len_utf8());
+        Span { file: self.file.clone(), line, column, start: index, end }
+    }
+
     pub fn peek(&mut self) -> Option<&(usize, char)> {
         self.chars.peek()
     }
 
-    pub fn invalid_symbol(&self, index: usize, chr: char) -> Result<Vec<Token>, SyntaxError> {
-        let line = self.line;
-        invalid_symbol_error!(line, index, "Unexpected character: {}", chr)
+    // Looks at the character after the next one.
+    pub fn peek_second(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next().map(|(_, chr)| chr)
+    }
+
+    pub fn invalid_symbol<T>(&self, index: usize, chr: char) -> Result<T, SyntaxError> {
+        let span = self.span_at(index);
+        invalid_symbol_error!(span, "Unexpected character: {}", chr)
     }
 
     pub fn next_char(&mut self, chr: char, buffer: &mut String) {
@@ -55,45 +96,219 @@ impl<'a> LexerIterator<'a> {
         self.next();
     }
 
-    pub fn next_number(&mut self, sign: char) -> Result<Token, SyntaxError> {
-        let mut number_buffer = String::new();
+    // Whether the input continues with a digit or a dot followed by one.
+    pub fn starts_number(&mut self) -> bool {
+        match self.peek() {
+            Some(&(_, '0'..='9')) => true,
+            Some(&(_, '.')) => self.peek_second().is_some_and(|chr| chr.is_ascii_digit()),
+            _ => false
+        }
+    }
+
+    // Whether the next character is a dot standing on its own, as in the
+    // dotted pair `(a . b)`, rather than the start of an identifier. The
+    // datum after it may follow without a space, as in `(a .(b))`.
+    pub fn starts_dot(&self) -> bool {
+        matches!(
+            self.peek_second(),
+            None | Some(' ' | '\x09' | '\x0a' | '\x0d' | '(' | ')' | ';' | '"' | '\'' | '`' | ',')
+        )
+    }
+
+    fn next_digits(&mut self, buffer: &mut String) {
+        while let Some(&(_, ch)) = self.peek() {
+            match ch {
+                '0'..='9' => self.next_char(ch, buffer),
+                _ => break
+            }
+        }
+    }
+
+    // Reads the digits of a number following its sign: an integer, a
+    // `numerator/denominator` rational or a decimal with an optional
+    // fraction and exponent.
+    pub fn next_number(&mut self, sign: char) -> Result<TokenKind, SyntaxError> {
+        let index = self.peek().map_or(0, |&(index, _)| index);
+        let mut number_buffer = sign.to_string();
+
+        self.next_digits(&mut number_buffer);
+        if let Some(&(_, '/')) = self.peek() {
+            self.next_char('/', &mut number_buffer);
+            self.next_digits(&mut number_buffer);
+        } else {
+            if let Some(&(_, '.')) = self.peek() {
+                self.next_char('.', &mut number_buffer);
+                self.next_digits(&mut number_buffer);
+            }
+            if let Some(&(_, ch @ ('e' | 'E'))) = self.peek() {
+                self.next_char(ch, &mut number_buffer);
+                if let Some(&(_, ch @ ('+' | '-'))) = self.peek() {
+                    self.next_char(ch, &mut number_buffer);
+                }
+                self.next_digits(&mut number_buffer);
+            }
+        }
+
+        match Number::parse(&number_buffer, 10) {
+            Some(number) => Ok(Number(number)),
+            None => {
+                let span = self.span_at(index);
+                invalid_symbol_error!(span, "Invalid number: {}", number_buffer)
+            }
+        }
+    }
+
+    // Reads `inf.0` or `nan.0` following a sign.
+    pub fn next_special_real(&mut self, sign: char) -> Result<TokenKind, SyntaxError> {
+        let (index, chr) = *self.peek().unwrap();
+        let mut buffer = sign.to_string();
 
         while let Some(&(_, ch)) = self.peek() {
             match ch {
-                '0' ... '9' => self.next_char(ch, &mut number_buffer),
+                'a'..='z' | '.' | '0'..='9' => self.next_char(ch, &mut buffer),
                 _ => break
             }
         }
 
-        let value = number_buffer.parse().unwrap();
-        Ok(Integer(if sign == '-' { -1 * value } else { value }))
+        match Number::parse(&buffer, 10) {
+            Some(number) => Ok(Number(number)),
+            None => {
+                let span = self.span_at(index);
+                invalid_symbol_error!(
+                    span,
+                    "Unexpected symbol '{}'. Expected white space or closing paren.",
+                    chr
+                )
+            }
+        }
     }
 
-    pub fn next_boolean(&mut self) -> Result<Token, SyntaxError> {
+    // Reads a number starting with radix and exactness prefixes, like `#x1F`
+    // or `#e#b101`. The digits run up to the next delimiter.
+    pub fn next_prefixed_number(&mut self) -> Result<TokenKind, SyntaxError> {
+        let index = self.peek().map_or(0, |&(index, _)| index);
+        let mut number_buffer = String::new();
+
+        while let Some(&(_, ch)) = self.peek() {
+            match ch {
+                '(' | ')' | '"' | ';' | ' ' | '\x09' | '\x0a' | '\x0d' => break,
+                _ => self.next_char(ch, &mut number_buffer)
+            }
+        }
+
+        match Number::parse_literal(&number_buffer, 10) {
+            Some(number) => Ok(Number(number)),
+            None => {
+                let span = self.span_at(index);
+                invalid_symbol_error!(span, "Invalid number: {}", number_buffer)
+            }
+        }
+    }
+
+    // Reads a `#\` character literal. The first character is taken as is,
+    // even if it is a delimiter; anything following it up to the next
+    // delimiter makes a character name or a hexadecimal scalar value.
+    pub fn next_character(&mut self) -> Result<TokenKind, SyntaxError> {
+        self.next();
+        self.next();
+        let index = self.peek().map_or(0, |&(index, _)| index);
+
+        let mut buffer = match self.next() {
+            Some((_, chr)) => chr.to_string(),
+            None => return Err(SyntaxError::UnexpectedEOL)
+        };
+        while let Some(&(_, ch)) = self.peek() {
+            match ch {
+                '(' | ')' | '"' | ';' | ' ' | '\x09' | '\x0a' | '\x0d' => break,
+                _ => self.next_char(ch, &mut buffer)
+            }
+        }
+
+        match character::parse(&buffer) {
+            Some(chr) => Ok(Character(chr)),
+            None => {
+                let span = self.span_at(index);
+                invalid_symbol_error!(span, "Unknown character name: {}", buffer)
+            }
+        }
+    }
+
+    // Reads the `#u8(` opening a bytevector.
+    pub fn next_bytevector_open(&mut self) -> Result<TokenKind, SyntaxError> {
+        let (index, _) = *self.peek().unwrap();
+        let mut buffer = String::new();
+
+        while let Some(&(_, ch)) = self.peek() {
+            self.next_char(ch, &mut buffer);
+            if buffer.len() == 4 || ch == '(' { break }
+        }
+
+        if buffer == "#u8(" {
+            Ok(OpenBytevector)
+        } else {
+            let span = self.span_at(index);
+            invalid_symbol_error!(span, "Unexpected symbol '{}'. Expected '#u8('.", buffer)
+        }
+    }
+
+    // Skips a `;` comment, up to the end of its line.
+    pub fn skip_line_comment(&mut self) {
+        while let Some(&(_, ch)) = self.peek() {
+            if ch == '\x0a' { break }
+            self.next();
+        }
+    }
+
+    // Skips a `#| ... |#` comment. Block comments nest, so every `#|` inside
+    // the comment needs its own `|#`.
+    pub fn skip_block_comment(&mut self) -> Result<(), SyntaxError> {
+        let start = self.offset();
+        let mut depth = 0;
+
+        while let Some((_, ch)) = self.next() {
+            match (ch, self.peek().map(|&(_, next)| next)) {
+                ('#', Some('|')) => {
+                    self.next();
+                    depth += 1;
+                },
+                ('|', Some('#')) => {
+                    self.next();
+                    depth -= 1;
+                    if depth == 0 { return Ok(()) }
+                },
+                _ => ()
+            }
+        }
+
+        let span = self.span_at(start).to(&self.span_at(start + 1));
+        Err(SyntaxError::CommentNotClosed(span))
+    }
+
+    pub fn next_boolean(&mut self) -> Result<TokenKind, SyntaxError> {
         self.next();
         match self.next() {
             Some((_, 't')) => { Ok(Boolean(true)) },
             Some((_, 'f')) => { Ok(Boolean(false)) },
             Some((index, symbol)) => {
-                let line = self.line;
+                let span = self.span_at(index);
                 invalid_symbol_error!(
-                    line, index,
+                    span,
                     "Unexpected character when looking for t/f: {:?}", symbol
                 )
             },
-            None => return Err(SyntaxError::UnexpectedEOL),
+            None => Err(SyntaxError::UnexpectedEOL),
         }
     }
 
-    pub fn next_identifier(&mut self) -> Result<Token, SyntaxError> {
+    pub fn next_identifier(&mut self) -> Result<TokenKind, SyntaxError> {
         let mut id_buffer = String::new();
 
         while let Some(&(_, chr)) = self.peek() {
             match chr {
-                'A' ... 'Z' | 'a' ... 'z' | '0' ... '9' => {
+                'A'..='Z' | 'a'..='z' | '0'..='9' => {
                     self.next_char(chr, &mut id_buffer);
                 },
-                '/' | '!' | '$' | '%' | '*' | ':' | '<' ... '?' | '_' | '-' | '+' => {
+                '/' | '!' | '$' | '%' | '*' | ':' | '<'..='?' | '_' | '-' | '+' | '.' => {
                     self.next_char(chr, &mut id_buffer);
                 },
                 _ => break
@@ -104,36 +319,94 @@ impl<'a> LexerIterator<'a> {
     }
 
 
-    pub fn next_string(&mut self) -> Result<Token, SyntaxError> {
+    // Reads a string literal, resolving its escape sequences.
+    pub fn next_string(&mut self) -> Result<TokenKind, SyntaxError> {
+        let start = self.offset();
         self.next();
         let mut string_buffer = String::new();
 
-        while let Some((_, chr)) = self.next() {
-            if chr == '\"' {
-                return Ok(StringToken(string_buffer))
-            } else {
-                string_buffer.push(chr);
+        while let Some((index, chr)) = self.next() {
+            match chr {
+                '\"' => return Ok(StringToken(string_buffer)),
+                '\\' => self.next_escape(start, index, &mut string_buffer)?,
+                _ => string_buffer.push(chr)
             }
         }
 
-        Err(SyntaxError::StringNotClosed)
+        Err(SyntaxError::StringNotClosed(self.span_at(start)))
     }
 
+    // Reads the escape sequence following a backslash in a string. A
+    // backslash followed by a line ending, optionally surrounded by spaces or
+    // tabs, continues the string on the next line without the white space.
+    fn next_escape(
+        &mut self, start: usize, index: usize, buffer: &mut String
+    ) -> Result<(), SyntaxError> {
+        let chr = match self.next() {
+            Some((_, chr)) => chr,
+            None => return Err(SyntaxError::StringNotClosed(self.span_at(start)))
+        };
 
-    pub fn next_delim(&mut self) -> Result<Option<Token>, SyntaxError> {
-        let mut result = None;
+        match chr {
+            'a' => buffer.push('\x07'),
+            'b' => buffer.push('\x08'),
+            't' => buffer.push('\t'),
+            'n' => buffer.push('\n'),
+            'r' => buffer.push('\r'),
+            '"' | '\\' | '|' => buffer.push(chr),
+            'x' | 'X' => {
+                let mut hex = String::new();
+                while let Some(&(_, ch)) = self.peek() {
+                    if !ch.is_ascii_hexdigit() { break }
+                    self.next_char(ch, &mut hex);
+                }
 
+                let scalar = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                match (scalar, self.peek()) {
+                    (Some(scalar), Some(&(_, ';'))) => {
+                        self.next();
+                        buffer.push(scalar)
+                    },
+                    _ => {
+                        let span = self.span_at(index);
+                        invalid_symbol_error!(span, "Invalid hex escape in string: \\x{}", hex)
+                    }
+                }
+            },
+            ' ' | '\t' | '\r' | '\n' => {
+                let mut line_ending = chr == '\n';
+                while let Some(&(_, ch @ (' ' | '\t' | '\r' | '\n'))) = self.peek() {
+                    if ch == '\n' {
+                        if line_ending { break }
+                        line_ending = true;
+                    }
+                    self.next();
+                }
+
+                if !line_ending {
+                    let span = self.span_at(index);
+                    invalid_symbol_error!(span, "Expected a line ending after '\\' in string")
+                }
+            },
+            _ => {
+                let span = self.span_at(index);
+                invalid_symbol_error!(span, "Invalid escape in string: \\{}", chr)
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks that a token is followed by a delimiter, which is left for the
+    // next token.
+    pub fn expect_delim(&mut self) -> Result<(), SyntaxError> {
         if let Some(&(index, c)) = self.peek() {
             match c {
-                ')' => {
-                    result = Some(CloseParen);
-                    self.next();
-                },
-                ' ' | '\x09' | '\x0a' | '\x0d' => (),
+                ')' | ' ' | '\x09' | '\x0a' | '\x0d' | ';' => (),
                 _ => {
-                    let line = self.line;
+                    let span = self.span_at(index);
                     invalid_symbol_error!(
-                        line, index,
+                        span,
                         "Unexpected symbol '{}'. Expected white space or closing paren.",
                         c
                     )
@@ -141,6 +414,6 @@ impl<'a> LexerIterator<'a> {
             }
         }
 
-        Ok(result)
+        Ok(())
     }
 }
@@ -1,69 +1,130 @@
 pub mod token;
 mod iterator;
 
+use std::rc::Rc;
+
 use errors::SyntaxError;
+use span::Span;
 use self::token::Token;
+use self::token::TokenKind;
 use self::iterator::LexerIterator;
 
 pub fn tokenize(input: &str) -> Result<Vec<Token>, SyntaxError> {
+    tokenize_source(input, "<input>")
+}
+
+// Splits the source into tokens, each one with the span of the source it
+// was read from. The file names the source in these spans.
+pub fn tokenize_source(input: &str, file: &str) -> Result<Vec<Token>, SyntaxError> {
+    let file = Rc::new(file.to_string());
     let mut tokens = Vec::new();
-    let mut it = LexerIterator::new(input);
+    let mut it = LexerIterator::new(input, file.clone());
 
     while let Some(&(index, c)) = it.peek() {
-         match c {
-                '(' => {
-                    tokens.push(Token::OpenParen);
-                    it.next();
-                },
-                ')' => {
-                    tokens.push(Token::CloseParen);
-                    it.next();
-                },
-                '+' | '-' => {
-                    it.next();
-                    match it.peek() {
-                        Some(&(_, '0' ... '9')) => {
-                            tokens.push(it.next_number(c)?);
-
-                            if let Some(token) = try!(it.next_delim()) {
-                                tokens.push(token);
-                            }
-                        },
-                        _ => {
-                            tokens.push(Token::Identifier(c.to_string()));
-                            if let Some(token) = try!(it.next_delim()) {
-                                tokens.push(token);
-                            }
-                        }
-                    }
-                },
-                '#' => {
-                    tokens.push(try!(it.next_boolean()));
-                    if let Some(token) = try!(it.next_delim()) {
-                        tokens.push(token);
-                    }
-                },
-                'A' ... 'Z' | 'a' ... 'z' | '!' | '$' | '%' | '&' | '*' | '/' | ':' | '<' ... '?' | '_' | '^'  => {
-                    tokens.push(it.next_identifier()?);
-                    if let Some(token) = try!(it.next_delim()) {
-                        tokens.push(token);
-                    }
-                },
-                '0' ... '9' => {
-                    tokens.push(it.next_number('+')?);
-                    if let Some(token) = try!(it.next_delim()) {
-                        tokens.push(token);
-                    }
-                },
-                '\"' => {
-                    tokens.push(it.next_string()?);
-                    if let Some(token) = try!(it.next_delim()) {
-                        tokens.push(token);
-                    }
-                },
-                ' ' | '\x09' | '\x0a' | '\x0d' => { it.next(); },
-                _  => return it.invalid_symbol(index, c)
-            }
+        match c {
+            ';' => {
+                it.skip_line_comment();
+                continue
+            },
+            '#' if it.peek_second() == Some('|') => {
+                it.skip_block_comment()?;
+                continue
+            },
+            ' ' | '\x09' | '\x0a' | '\x0d' => {
+                it.next();
+                continue
+            },
+            _ => ()
+        }
+
+        let (line, column) = it.location();
+        let kind = match c {
+            '(' => {
+                it.next();
+                TokenKind::OpenParen
+            },
+            ')' => {
+                it.next();
+                TokenKind::CloseParen
+            },
+            '\'' => {
+                it.next();
+                TokenKind::Quote
+            },
+            '`' => {
+                it.next();
+                TokenKind::Quasiquote
+            },
+            ',' if it.peek_second() == Some('@') => {
+                it.next();
+                it.next();
+                TokenKind::UnquoteSplicing
+            },
+            ',' => {
+                it.next();
+                TokenKind::Unquote
+            },
+            '+' | '-' => {
+                it.next();
+                let kind = match it.peek().map(|&(_, next)| next) {
+                    Some('0'..='9') | Some('.') if it.starts_number() => it.next_number(c)?,
+                    Some('i') | Some('n') => it.next_special_real(c)?,
+                    _ => TokenKind::Identifier(c.to_string())
+                };
+                it.expect_delim()?;
+                kind
+            },
+            '#' if it.peek_second() == Some(';') => {
+                it.next();
+                it.next();
+                TokenKind::DatumComment
+            },
+            '#' if it.peek_second() == Some('(') => {
+                it.next();
+                it.next();
+                TokenKind::OpenVector
+            },
+            '#' if it.peek_second() == Some('u') => it.next_bytevector_open()?,
+            '#' => {
+                let kind = match it.peek_second() {
+                    Some('b' | 'o' | 'd' | 'x' | 'e' | 'i' | 'B' | 'O' | 'D' | 'X' | 'E' | 'I') => {
+                        it.next_prefixed_number()?
+                    },
+                    Some('\\') => it.next_character()?,
+                    _ => it.next_boolean()?
+                };
+                it.expect_delim()?;
+                kind
+            },
+            '.' if it.starts_dot() => {
+                it.next();
+                TokenKind::Dot
+            },
+            '.' if it.starts_number() => {
+                let kind = it.next_number('+')?;
+                it.expect_delim()?;
+                kind
+            },
+            'A'..='Z' | 'a'..='z' | '!' | '$' | '%' | '&' | '*' | '/' | ':' | '<'..='?' | '_' | '^' | '.' => {
+                let kind = it.next_identifier()?;
+                it.expect_delim()?;
+                kind
+            },
+            '0'..='9' => {
+                let kind = it.next_number('+')?;
+                it.expect_delim()?;
+                kind
+            },
+            '\"' => {
+                let kind = it.next_string()?;
+                it.expect_delim()?;
+                kind
+            },
+            _  => return it.invalid_symbol(index, c)
+        };
+
+        let span = Span { file: file.clone(), line, column, start: index, end: it.offset() };
+        tokens.push(Token { kind, span });
     }
 
     Ok(tokens)
@@ -71,31 +132,40 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>, SyntaxError> {
 
 #[cfg(test)]
 mod tests {
-    use super::token::Token;
-    use super::token::Token::*;
+    use std::f64;
+
+    use number::Number::Integer;
+    use number::Number::Real;
+
+    use super::token::TokenKind;
+    use super::token::TokenKind::*;
 
     use super::tokenize;
 
-    fn id(id: &str) -> Token { Identifier(id.to_string()) }
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        tokenize(input).unwrap().into_iter().map(|token| token.kind).collect()
+    }
+    fn id(id: &str) -> TokenKind { Identifier(id.to_string()) }
+    fn int(n: i64) -> TokenKind { Number(Integer(n)) }
 
     #[test]
     fn test_simple_expression_tokenize() {
         assert_eq!(
-            tokenize("(+ 1 4)").unwrap(),
+            kinds("(+ 1 4)"),
             vec![
-                OpenParen, id("+"), Integer(1), Integer(4), CloseParen
+                OpenParen, id("+"), int(1), int(4), CloseParen
             ]
         );
 
         assert_eq!(
-            tokenize("(-5)").unwrap(),
-            vec![OpenParen, Integer(-5), CloseParen]
+            kinds("(-5)"),
+            vec![OpenParen, int(-5), CloseParen]
         );
     }
 
     #[test]
     fn test_tokenize_invalid_syntax() {
-        assert!(tokenize("(')").is_err());
+        assert!(tokenize("(@)").is_err());
         assert!(tokenize("+%").is_err());
         assert!(tokenize("(-23+)").is_err());
         assert!(tokenize("(24r2+)").is_err())
@@ -104,10 +174,10 @@ mod tests {
     #[test]
     fn test_white_space_tokenize() {
         assert_eq!(
-            tokenize("(+ 3    2)\n(-  \n \t   2\t1 \t)\r\n \t \n").unwrap(),
+            kinds("(+ 3    2)\n(-  \n \t   2\t1 \t)\r\n \t \n"),
             vec![
-                OpenParen, id("+"), Integer(3), Integer(2), CloseParen,
-                OpenParen, id("-"), Integer(2), Integer(1), CloseParen
+                OpenParen, id("+"), int(3), int(2), CloseParen,
+                OpenParen, id("-"), int(2), int(1), CloseParen
             ]
         )
     }
@@ -115,55 +185,251 @@ mod tests {
     #[test]
     fn test_tokenize_strings() {
         assert_eq!(
-            tokenize("\"Sh!t and f*ck & stu$$$6!\"").unwrap(),
+            kinds("\"Sh!t and f*ck & stu$$$6!\""),
             vec![StringToken("Sh!t and f*ck & stu$$$6!".to_string())]
         );
 
         assert!(tokenize("\"down, down").is_err());
     }
 
+    #[test]
+    fn test_tokenize_string_escapes() {
+        let string = |text: &str| vec![StringToken(text.to_string())];
+
+        assert_eq!(kinds(r#""say \"hi\"""#), string("say \"hi\""));
+        assert_eq!(kinds(r#""a\\b\|""#), string("a\\b|"));
+        assert_eq!(kinds(r#""\a\b\t\n\r""#), string("\x07\x08\t\n\r"));
+        assert_eq!(kinds(r#""\x41;\x3bb;""#), string("Aλ"));
+        assert_eq!(kinds("\"one \\  \n    two\""), string("one two"));
+        assert_eq!(kinds("\"one\\\n\n two\""), string("one\n two"));
+
+        assert!(tokenize(r#""\q""#).is_err());
+        assert!(tokenize(r#""\x41""#).is_err());
+        assert!(tokenize(r#""\xD800;""#).is_err());
+        assert!(tokenize(r#""a \ b""#).is_err());
+        assert!(tokenize(r#""\"#).is_err());
+    }
+
 
     #[test]
     fn test_integer_tokenize() {
         assert_eq!(
-            tokenize("(+ -4 +1 -713 -5 6)").unwrap(),
+            kinds("(+ -4 +1 -713 -5 6)"),
             vec![
                 OpenParen,
                 id("+"),
-                Integer(-4),
-                Integer(1),
-                Integer(-713),
-                Integer(-5),
-                Integer(6),
+                int(-4),
+                int(1),
+                int(-713),
+                int(-5),
+                int(6),
                 CloseParen
             ]
         );
 
         assert_eq!(
-            tokenize("(- 7 49)").unwrap(),
-            vec![OpenParen, id("-"), Integer(7), Integer(49), CloseParen]
+            kinds("(- 7 49)"),
+            vec![OpenParen, id("-"), int(7), int(49), CloseParen]
         );
 
         assert_eq!(
-            tokenize("( - 778899 (+ 2131 4362))").unwrap(),
+            kinds("( - 778899 (+ 2131 4362))"),
             vec![
-                OpenParen, id("-"), Integer(778899), OpenParen, id("+"),
-                Integer(2131), Integer(4362), CloseParen, CloseParen
+                OpenParen, id("-"), int(778899), OpenParen, id("+"),
+                int(2131), int(4362), CloseParen, CloseParen
             ]
         )
     }
 
+    #[test]
+    fn test_number_tokenize() {
+        assert_eq!(
+            kinds("(+ 1.5 -.25 +2. 1e3 -2.5e-1)"),
+            vec![
+                OpenParen, id("+"), Number(Real(1.5)), Number(Real(-0.25)),
+                Number(Real(2.0)), Number(Real(1000.0)), Number(Real(-0.25)), CloseParen
+            ]
+        );
+
+        assert_eq!(
+            kinds("+inf.0 -inf.0"),
+            vec![Number(Real(f64::INFINITY)), Number(Real(f64::NEG_INFINITY))]
+        );
+
+        match kinds("1/3 -6/3 +nan.0").as_slice() {
+            [Number(third), two, Number(nan)] => {
+                assert_eq!(third.to_string(), "1/3");
+                assert_eq!(*two, int(-2));
+                assert!(nan.to_f64().is_nan());
+            },
+            tokens => panic!("Unexpected tokens: {:?}", tokens)
+        }
+
+        assert_eq!(
+            kinds("123456789012345678901234567890")[0],
+            Number("123456789012345678901234567890".parse().map(::number::Number::Big).unwrap())
+        );
+
+        assert!(tokenize("1/0").is_err());
+        assert!(tokenize("1.2.3").is_err());
+        assert!(tokenize("1e").is_err());
+        assert!(tokenize("+inf").is_err());
+        assert!(tokenize("-nan.1").is_err());
+    }
+
+    #[test]
+    fn test_prefixed_number_tokenize() {
+        assert_eq!(
+            kinds("(+ #x1F #b-101 #o17 #d9)"),
+            vec![OpenParen, id("+"), int(31), int(-5), int(15), int(9), CloseParen]
+        );
+
+        assert_eq!(
+            kinds("(#e1.5 #i3 #x#e10)"),
+            vec![
+                OpenParen, Number(::number::Number::parse("3/2", 10).unwrap()),
+                Number(Real(3.0)), int(16), CloseParen
+            ]
+        );
+
+        assert!(tokenize("#x1G").is_err());
+        assert!(tokenize("#b2").is_err());
+        assert!(tokenize("#e#e1").is_err());
+        assert!(tokenize("#x").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_characters() {
+        assert_eq!(
+            kinds("(#\\a #\\space #\\x41 #\\x #\\( #\\) #\\λ)"),
+            vec![
+                OpenParen, Character('a'), Character(' '), Character('A'), Character('x'),
+                Character('('), Character(')'), Character('λ'), CloseParen
+            ]
+        );
+        assert_eq!(kinds("#\\ "), vec![Character(' ')]);
+
+        assert!(tokenize("#\\spaces").is_err());
+        assert!(tokenize("#\\xZZ").is_err());
+        assert!(tokenize("#\\").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_vectors() {
+        assert_eq!(
+            kinds("#(1 #(2) (3))"),
+            vec![
+                OpenVector, int(1), OpenVector, int(2), CloseParen,
+                OpenParen, int(3), CloseParen, CloseParen
+            ]
+        );
+        assert_eq!(kinds("#()"), vec![OpenVector, CloseParen]);
+    }
+
+    #[test]
+    fn test_tokenize_bytevectors() {
+        assert_eq!(
+            kinds("#u8(1 255)"),
+            vec![OpenBytevector, int(1), int(255), CloseParen]
+        );
+
+        assert!(tokenize("#u9(1)").is_err());
+        assert!(tokenize("#u8").is_err());
+    }
+
     #[test]
     fn test_identifiers() {
-        for identifier in ["+", ">=", "ho!", "unless", "it", "$salam"].iter() {
-            assert_eq!(tokenize(*identifier).unwrap(), vec![id(identifier)])
+        for identifier in ["+", ">=", "ho!", "unless", "it", "$salam", "...", "a.b"].iter() {
+            assert_eq!(kinds(identifier), vec![id(identifier)])
         }
     }
 
+    #[test]
+    fn test_tokenize_dots() {
+        assert_eq!(
+            kinds("(a . rest)"),
+            vec![OpenParen, id("a"), Dot, id("rest"), CloseParen]
+        );
+        assert_eq!(
+            kinds("(a .\n.5 ...)"),
+            vec![OpenParen, id("a"), Dot, Number(Real(0.5)), id("..."), CloseParen]
+        );
+        assert_eq!(kinds("(1 .)"), vec![OpenParen, int(1), Dot, CloseParen]);
+        assert_eq!(
+            kinds("(a .(b))"),
+            vec![OpenParen, id("a"), Dot, OpenParen, id("b"), CloseParen, CloseParen]
+        );
+        assert_eq!(kinds("(a .'b)"), vec![OpenParen, id("a"), Dot, Quote, id("b"), CloseParen]);
+        assert_eq!(
+            kinds("(a .\"s\" .`b .,c)"),
+            vec![
+                OpenParen, id("a"), Dot, StringToken("s".to_string()), Dot, Quasiquote, id("b"),
+                Dot, Unquote, id("c"), CloseParen
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_quotes() {
+        assert_eq!(
+            kinds("'a `(b ,c ,@d) ''()"),
+            vec![
+                Quote, id("a"), Quasiquote, OpenParen, id("b"), Unquote, id("c"),
+                UnquoteSplicing, id("d"), CloseParen, Quote, Quote, OpenParen, CloseParen
+            ]
+        );
+        assert_eq!(kinds("'#(1)"), vec![Quote, OpenVector, int(1), CloseParen]);
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        assert_eq!(
+            kinds("; a comment\n(+ 1 ; another\n 2);the end"),
+            vec![OpenParen, id("+"), int(1), int(2), CloseParen]
+        );
+        assert_eq!(
+            kinds("(a #| one #| nested |# |# b #||#)"),
+            vec![OpenParen, id("a"), id("b"), CloseParen]
+        );
+        assert_eq!(
+            kinds("(a #;(b c) . #;d e)"),
+            vec![
+                OpenParen, id("a"), DatumComment, OpenParen, id("b"), id("c"), CloseParen,
+                Dot, DatumComment, id("d"), id("e"), CloseParen
+            ]
+        );
+        assert_eq!(
+            kinds("\"a ; b\" #\\;"),
+            vec![StringToken("a ; b".to_string()), Character(';')]
+        );
+
+        assert_eq!(
+            tokenize("#| one #| two |#").err().unwrap().to_string(),
+            "SyntaxError: Block comment is not properly closed (line: 1, column: 1)"
+        );
+    }
+
+    #[test]
+    fn test_token_spans() {
+        let tokens = tokenize("(a\n  \"λx\" 1)").unwrap();
+        assert_eq!(
+            tokens.iter().map(|token| {
+                (token.span.line, token.span.column, token.span.start, token.span.end)
+            }).collect::<Vec<_>>(),
+            vec![(1, 0, 0, 1), (1, 1, 1, 2), (2, 2, 5, 10), (2, 7, 11, 12), (2, 8, 12, 13)]
+        );
+        assert_eq!(*tokens[0].span.file, "<input>");
+
+        assert_eq!(
+            tokenize("(a\n  #q)").err().unwrap().to_string(),
+            "SyntaxError: Unexpected character when looking for t/f: 'q' (line: 2, column: 4)"
+        );
+    }
+
     #[test]
     fn test_tokenize_booleans() {
-        assert_eq!(tokenize("#t").unwrap(), vec![Boolean(true)]);
-        assert_eq!(tokenize("#f").unwrap(), vec![Boolean(false)]);
+        assert_eq!(kinds("#t"), vec![Boolean(true)]);
+        assert_eq!(kinds("#f"), vec![Boolean(false)]);
 
         assert!(tokenize("#a").is_err());
         assert!(tokenize("#T").is_err());
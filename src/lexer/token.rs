@@ -1,9 +1,28 @@
+use number::Number;
+use span::Span;
+
 #[derive(PartialEq, Debug)]
-pub enum Token {
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
+pub enum TokenKind {
     OpenParen,
+    OpenVector,
+    OpenBytevector,
     CloseParen,
+    Dot,
+    Quote,
+    Quasiquote,
+    Unquote,
+    UnquoteSplicing,
+    DatumComment,
     Identifier(String),
-    Integer(isize),
+    Number(Number),
     Boolean(bool),
+    Character(char),
     StringToken(String)
 }
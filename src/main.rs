@@ -1,17 +1,36 @@
 extern crate rustyline;
 extern crate colored;
+extern crate num_bigint;
+extern crate num_integer;
+extern crate num_rational;
+extern crate num_traits;
 
 #[macro_use]
 mod errors;
 
+mod span;
+mod diagnostic;
+mod number;
+mod character;
 mod lexer;
 mod parser;
 mod interpreter;
 mod repl;
 
+use std::process;
+use std::thread;
+
 use colored::*;
 
 fn main() {
-    println!("{}", "\nInteractive MinScheme (0.1.0) - press Ctrl+C to exit\n".blue());
-    repl::start("> ", (|s, runtime| interpreter::run(&s, runtime)))
+    // The REPL runs in a thread of its own, as the main thread's stack is too
+    // small for the evaluations the interpreter allows to be nested.
+    let repl = thread::Builder::new().stack_size(interpreter::STACK_SIZE).spawn(|| {
+        println!("{}", "\nInteractive MinScheme (0.1.0) - press Ctrl+C to exit\n".blue());
+        repl::start("> ", |s, runtime| interpreter::run_with_diagnostics(&s, runtime))
+    }).expect("Can't start the REPL thread");
+
+    if repl.join().is_err() {
+        process::exit(1)
+    }
 }
@@ -0,0 +1,630 @@
+use std::cmp::Ordering;
+use std::f64;
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_integer::Integer as IntegerOps;
+use num_rational::BigRational;
+use num_traits::Pow;
+use num_traits::ToPrimitive;
+use num_traits::Zero;
+
+// The numeric tower: fixnums are promoted to bignums when an operation
+// overflows, exact division produces rationals and reals are inexact.
+// Bignums and rationals are always kept normalized, so a Big never fits in
+// an i64 and a Rational never has a denominator of one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Big(BigInt),
+    Rational(BigRational),
+    Real(f64),
+}
+
+use self::Number::*;
+
+// The largest exact power computed, in bits. Bigger ones, like
+// `(expt 2 1000000000000)`, would take too long or run out of memory.
+const MAX_POWER_BITS: u64 = 1 << 20;
+
+impl Number {
+    pub fn from_bigint(value: BigInt) -> Number {
+        match value.to_i64() {
+            Some(x) => Integer(x),
+            None => Big(value)
+        }
+    }
+
+    pub fn from_ratio(value: BigRational) -> Number {
+        if value.is_integer() {
+            Number::from_bigint(value.to_integer())
+        } else {
+            Rational(value)
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        !matches!(*self, Real(_))
+    }
+
+    // Same exactness and value; reals are compared bit for bit, so 0.0 and
+    // -0.0 differ. Exact numbers are normalized and can be compared as is.
+    pub fn is_eqv(&self, other: &Number) -> bool {
+        match (self, other) {
+            (&Real(a), &Real(b)) => a.to_bits() == b.to_bits(),
+            _ => self == other
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match *self {
+            Integer(x) => x == 0,
+            Real(x) => x == 0.0,
+            _ => false
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.compare(&Integer(0)) == Some(Ordering::Less)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            Integer(x) => x as f64,
+            Big(ref x) => x.to_f64().unwrap_or(f64::NAN),
+            Rational(ref x) => x.to_f64().unwrap_or(f64::NAN),
+            Real(x) => x
+        }
+    }
+
+    // The exact value of the number as a fraction; None for infinities
+    // and NaN, which have no exact counterpart.
+    fn to_ratio(&self) -> Option<BigRational> {
+        match *self {
+            Integer(x) => Some(BigRational::from_integer(BigInt::from(x))),
+            Big(ref x) => Some(BigRational::from_integer(x.clone())),
+            Rational(ref x) => Some(x.clone()),
+            Real(x) => BigRational::from_float(x)
+        }
+    }
+
+    fn to_bigint(&self) -> Option<BigInt> {
+        match *self {
+            Integer(x) => Some(BigInt::from(x)),
+            Big(ref x) => Some(x.clone()),
+            _ => None
+        }
+    }
+
+    pub fn to_exact(&self) -> Option<Number> {
+        if self.is_exact() {
+            Some(self.clone())
+        } else {
+            self.to_ratio().map(Number::from_ratio)
+        }
+    }
+
+    pub fn to_inexact(&self) -> Number {
+        Real(self.to_f64())
+    }
+
+    // Applies a binary operation at the level of the tower both arguments
+    // fit in, falling back to bignums when the fixnum operation overflows.
+    fn arithmetic(
+        &self,
+        other: &Number,
+        fixnum: fn(i64, i64) -> Option<i64>,
+        big: fn(BigInt, BigInt) -> BigInt,
+        ratio: fn(BigRational, BigRational) -> BigRational,
+        real: fn(f64, f64) -> f64
+    ) -> Number {
+        match (self, other) {
+            (&Integer(a), &Integer(b)) => match fixnum(a, b) {
+                Some(result) => Integer(result),
+                None => Number::from_bigint(big(BigInt::from(a), BigInt::from(b)))
+            },
+            (&Real(_), _) | (_, &Real(_)) => Real(real(self.to_f64(), other.to_f64())),
+            (&Rational(_), _) | (_, &Rational(_)) => Number::from_ratio(
+                ratio(self.to_ratio().unwrap(), other.to_ratio().unwrap())
+            ),
+            _ => Number::from_bigint(big(self.to_bigint().unwrap(), other.to_bigint().unwrap()))
+        }
+    }
+
+    pub fn add(&self, other: &Number) -> Number {
+        self.arithmetic(other, |a, b| a.checked_add(b), |a, b| a + b, |a, b| a + b, |a, b| a + b)
+    }
+
+    pub fn sub(&self, other: &Number) -> Number {
+        self.arithmetic(other, |a, b| a.checked_sub(b), |a, b| a - b, |a, b| a - b, |a, b| a - b)
+    }
+
+    pub fn mul(&self, other: &Number) -> Number {
+        self.arithmetic(other, |a, b| a.checked_mul(b), |a, b| a * b, |a, b| a * b, |a, b| a * b)
+    }
+
+    // Returns None when dividing by an exact zero, which is an error even if
+    // the dividend is inexact.
+    pub fn div(&self, other: &Number) -> Option<Number> {
+        if other.is_exact() && other.is_zero() {
+            return None
+        }
+
+        match (self, other) {
+            (&Real(_), _) | (_, &Real(_)) => Some(Real(self.to_f64() / other.to_f64())),
+            _ => Some(Number::from_ratio(self.to_ratio().unwrap() / other.to_ratio().unwrap()))
+        }
+    }
+
+    // Numeric comparison, exact whenever possible. None if either is NaN.
+    pub fn compare(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (&Integer(a), &Integer(b)) => Some(a.cmp(&b)),
+            (&Real(a), &Real(b)) => a.partial_cmp(&b),
+            (&Real(a), _) if !a.is_finite() => a.partial_cmp(&0.0),
+            (_, &Real(b)) if !b.is_finite() => 0.0.partial_cmp(&b),
+            _ => Some(self.to_ratio().unwrap().cmp(&other.to_ratio().unwrap()))
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        match *self {
+            Integer(_) | Big(_) => true,
+            Rational(_) => false,
+            Real(x) => x.is_finite() && x.fract() == 0.0
+        }
+    }
+
+    pub fn neg(&self) -> Number {
+        match *self {
+            Real(x) => Real(-x),
+            _ => Integer(0).sub(self)
+        }
+    }
+
+    pub fn abs(&self) -> Number {
+        if self.is_negative() { self.neg() } else { self.clone() }
+    }
+
+    // Integer division of two integers, the divisor being non-zero. The
+    // quotient is rounded towards negative infinity when `floor` is set and
+    // towards zero otherwise; the remainder has the sign of the divisor or
+    // the dividend respectively.
+    pub fn div_rem(&self, other: &Number, floor: bool) -> (Number, Number) {
+        match (self, other) {
+            (&Integer(a), &Integer(b)) if a.checked_rem(b).is_some() => {
+                let (quotient, remainder) = if floor { a.div_mod_floor(&b) } else { a.div_rem(&b) };
+                (Integer(quotient), Integer(remainder))
+            },
+            (&Real(_), _) | (_, &Real(_)) => {
+                let (a, b) = (self.to_f64(), other.to_f64());
+                let quotient = if floor { (a / b).floor() } else { (a / b).trunc() };
+                (Real(quotient), Real(a - b * quotient))
+            },
+            _ => {
+                let (a, b) = (self.to_bigint().unwrap(), other.to_bigint().unwrap());
+                let (quotient, remainder) = if floor { a.div_mod_floor(&b) } else { a.div_rem(&b) };
+                (Number::from_bigint(quotient), Number::from_bigint(remainder))
+            }
+        }
+    }
+
+    // Greatest common divisor of two integers, inexact if either is.
+    pub fn gcd(&self, other: &Number) -> Number {
+        self.integer_operation(other, |a, b| a.gcd(&b))
+    }
+
+    // Least common multiple of two integers, inexact if either is.
+    pub fn lcm(&self, other: &Number) -> Number {
+        self.integer_operation(other, |a, b| a.lcm(&b))
+    }
+
+    fn integer_operation(&self, other: &Number, operation: fn(BigInt, BigInt) -> BigInt) -> Number {
+        let exact = |x: &Number| x.to_exact().and_then(|x| x.to_bigint()).unwrap();
+        let result = Number::from_bigint(operation(exact(self), exact(other)));
+
+        if self.is_exact() && other.is_exact() { result } else { result.to_inexact() }
+    }
+
+    pub fn floor(&self) -> Number {
+        self.rounded(f64::floor, BigRational::floor)
+    }
+
+    pub fn ceiling(&self) -> Number {
+        self.rounded(f64::ceil, BigRational::ceil)
+    }
+
+    pub fn truncate(&self) -> Number {
+        self.rounded(f64::trunc, BigRational::trunc)
+    }
+
+    // Rounds to the nearest integer, ties going to the even one.
+    pub fn round(&self) -> Number {
+        self.rounded(f64::round_ties_even, |x| {
+            let floor = x.floor();
+            let two = BigRational::from_integer(BigInt::from(2));
+            match (x - &floor).cmp(&(BigRational::from_integer(BigInt::from(1)) / &two)) {
+                Ordering::Less => floor,
+                Ordering::Greater => floor + BigInt::from(1),
+                Ordering::Equal if (&floor / &two).is_integer() => floor,
+                Ordering::Equal => floor + BigInt::from(1)
+            }
+        })
+    }
+
+    fn rounded(&self, real: fn(f64) -> f64, ratio: fn(&BigRational) -> BigRational) -> Number {
+        match *self {
+            Rational(ref x) => Number::from_ratio(ratio(x)),
+            Real(x) => Real(real(x)),
+            _ => self.clone()
+        }
+    }
+
+    // Raises the number to the given power, exactly when both are exact and
+    // the exponent is an integer. Fails for an exact zero raised to a
+    // negative power and for exact results over MAX_POWER_BITS, which only
+    // 0, 1 and -1 stay under when the exponent is a bignum.
+    pub fn expt(&self, exponent: &Number) -> Result<Number, &'static str> {
+        if self.is_exact() && self.is_zero() && exponent.is_negative() {
+            return Err("Division by zero")
+        }
+
+        match *exponent {
+            Integer(e) if self.is_exact() => {
+                let base = self.to_ratio().unwrap();
+                let bits = base.numer().bits().max(base.denom().bits());
+                if bits > 1 && bits.saturating_mul(e.unsigned_abs()) > MAX_POWER_BITS {
+                    return Err("Exact result of 'expt' is too large")
+                }
+                Ok(Number::from_ratio(base.pow(e)))
+            },
+            Big(ref e) if self.is_exact() => match *self {
+                Integer(0) | Integer(1) => Ok(self.clone()),
+                Integer(-1) => Ok(Integer(if e.is_odd() { -1 } else { 1 })),
+                _ => Err("Exact result of 'expt' is too large")
+            },
+            _ => Ok(Real(self.to_f64().powf(exponent.to_f64())))
+        }
+    }
+
+    // The square root, exact for exact perfect squares. None for negative
+    // numbers, as there are no complex ones.
+    pub fn sqrt(&self) -> Option<Number> {
+        if self.is_negative() {
+            return None
+        }
+
+        if let Some(ratio) = self.to_ratio().filter(|_| self.is_exact()) {
+            let (numer, denom) = (ratio.numer().sqrt(), ratio.denom().sqrt());
+            if &numer * &numer == *ratio.numer() && &denom * &denom == *ratio.denom() {
+                return Some(Number::from_ratio(BigRational::new(numer, denom)))
+            }
+        }
+        Some(Real(self.to_f64().sqrt()))
+    }
+
+    // The largest integer whose square doesn't exceed the given non-negative
+    // exact integer, together with the rest.
+    pub fn exact_integer_sqrt(&self) -> (Number, Number) {
+        let x = self.to_bigint().unwrap();
+        let root = x.sqrt();
+        let rest = &x - &root * &root;
+        (Number::from_bigint(root), Number::from_bigint(rest))
+    }
+
+    // Renders the number in the given radix. Inexact numbers can only be
+    // written in radix 10.
+    pub fn to_string_radix(&self, radix: u32) -> Option<String> {
+        match *self {
+            _ if radix == 10 => Some(self.to_string()),
+            Integer(x) => Some(BigInt::from(x).to_str_radix(radix)),
+            Big(ref x) => Some(x.to_str_radix(radix)),
+            Rational(ref x) => Some(format!(
+                "{}/{}", x.numer().to_str_radix(radix), x.denom().to_str_radix(radix)
+            )),
+            Real(_) => None
+        }
+    }
+
+    // Parses a number literal, which may start with a radix (#b, #o, #d, #x)
+    // and an exactness (#e, #i) prefix in either order. Without a radix
+    // prefix the number is read in the given radix.
+    pub fn parse_literal(text: &str, radix: u32) -> Option<Number> {
+        let mut text = text;
+        let mut radix = radix;
+        let mut radix_prefix = false;
+        let mut exactness = None;
+
+        while let Some(rest) = text.strip_prefix('#') {
+            match rest.chars().next().map(|chr| chr.to_ascii_lowercase()) {
+                Some(chr @ ('b' | 'o' | 'd' | 'x')) if !radix_prefix => {
+                    radix = match chr { 'b' => 2, 'o' => 8, 'd' => 10, _ => 16 };
+                    radix_prefix = true;
+                },
+                Some(chr @ ('e' | 'i')) if exactness.is_none() => exactness = Some(chr),
+                _ => return None
+            }
+            text = &rest[1..];
+        }
+
+        match exactness {
+            Some('e') if radix == 10 && !text.contains('/') && text.contains(['.', 'e', 'E']) => {
+                parse_decimal(text)?;
+                exact_decimal(text).map(Number::from_ratio)
+            },
+            Some('e') => Number::parse(text, radix)?.to_exact(),
+            Some(_) => Number::parse(text, radix).map(|number| number.to_inexact()),
+            None => Number::parse(text, radix)
+        }
+    }
+
+    // Parses the textual representation of a number without any prefixes:
+    // integers, `n/d` rationals, decimals with an optional exponent (only
+    // in radix 10) and the `+inf.0`, `-inf.0` and `+nan.0` special values.
+    pub fn parse(text: &str, radix: u32) -> Option<Number> {
+        match text {
+            "+inf.0" => return Some(Real(f64::INFINITY)),
+            "-inf.0" => return Some(Real(f64::NEG_INFINITY)),
+            "+nan.0" | "-nan.0" => return Some(Real(f64::NAN)),
+            _ => ()
+        }
+
+        if let Some(slash) = text.find('/') {
+            let numerator = parse_integer(&text[..slash], radix)?;
+            let denominator = parse_integer(&text[slash + 1..], radix)?;
+            if denominator.is_zero() || text[slash + 1..].starts_with(['+', '-']) {
+                return None
+            }
+            Some(Number::from_ratio(BigRational::new(numerator, denominator)))
+        } else if radix == 10 && text.contains(['.', 'e', 'E']) {
+            parse_decimal(text).map(Real)
+        } else {
+            parse_integer(text, radix).map(Number::from_bigint)
+        }
+    }
+}
+
+fn parse_integer(text: &str, radix: u32) -> Option<BigInt> {
+    let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None
+    }
+    BigInt::parse_bytes(text.as_bytes(), radix)
+}
+
+fn parse_decimal(text: &str) -> Option<f64> {
+    let mut chars = text.chars().peekable();
+    let mut digits = 0;
+
+    if let Some(&('+' | '-')) = chars.peek() { chars.next(); }
+    while let Some(&c) = chars.peek() {
+        match c {
+            '0'..='9' => digits += 1,
+            '.' => (),
+            _ => break
+        }
+        chars.next();
+    }
+    if digits == 0 || text.matches('.').count() > 1 {
+        return None
+    }
+
+    if let Some('e' | 'E') = chars.next() {
+        if let Some(&('+' | '-')) = chars.peek() { chars.next(); }
+        if chars.peek().is_none() || !chars.all(|c| c.is_ascii_digit()) {
+            return None
+        }
+    }
+    text.parse().ok()
+}
+
+// The largest power of ten an exact decimal can be scaled by. Computing
+// bigger ones would take too long or run out of memory.
+const MAX_DECIMAL_EXPONENT: u64 = 100_000;
+
+// The exact value of a decimal literal already validated by parse_decimal,
+// or None if its exponent is out of range.
+fn exact_decimal(text: &str) -> Option<BigRational> {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(e) => (&text[..e], text[e + 1..].parse::<i64>().ok()?),
+        None => (text, 0)
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(dot) => (&mantissa[..dot], &mantissa[dot + 1..]),
+        None => (mantissa, "")
+    };
+
+    let digits = format!("{}{}", integer, fraction);
+    let digits = if digits.chars().all(|c| c == '+' || c == '-') {
+        format!("{}0", digits)
+    } else { digits };
+    let value = BigRational::from_integer(digits.parse::<BigInt>().unwrap());
+
+    let scale = exponent.checked_sub(fraction.len() as i64)?;
+    if scale.unsigned_abs() > MAX_DECIMAL_EXPONENT {
+        return None
+    }
+    let power = BigRational::from_integer(BigInt::from(10).pow(scale.unsigned_abs()));
+    Some(if scale < 0 { value / power } else { value * power })
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Integer(x) => write!(f, "{}", x),
+            Big(ref x) => write!(f, "{}", x),
+            Rational(ref x) => write!(f, "{}", x),
+            Real(x) if x.is_nan() => write!(f, "+nan.0"),
+            Real(x) if x.is_infinite() => write!(f, "{}inf.0", if x > 0.0 { "+" } else { "-" }),
+            Real(x) => write!(f, "{:?}", x)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64;
+
+    use num_bigint::BigInt;
+
+    use super::Number;
+    use super::Number::*;
+
+    fn parse(text: &str) -> Number { Number::parse(text, 10).unwrap() }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(parse("42"), Integer(42));
+        assert_eq!(parse("-42"), Integer(-42));
+        assert_eq!(parse("+7"), Integer(7));
+        assert_eq!(parse("1.5"), Real(1.5));
+        assert_eq!(parse(".5"), Real(0.5));
+        assert_eq!(parse("-2."), Real(-2.0));
+        assert_eq!(parse("1e10"), Real(1e10));
+        assert_eq!(parse("2.5E-3"), Real(0.0025));
+        assert_eq!(parse("4/2"), Integer(2));
+        assert_eq!(parse("-1/3").to_string(), "-1/3");
+        assert_eq!(parse("+inf.0"), Real(f64::INFINITY));
+        assert_eq!(parse("-inf.0"), Real(f64::NEG_INFINITY));
+        assert!(parse("+nan.0").to_f64().is_nan());
+        assert_eq!(
+            parse("123456789012345678901234567890").to_string(),
+            "123456789012345678901234567890"
+        );
+
+        for invalid in ["", "+", ".", "1/0", "1/-2", "1.2.3", "1e", "e5", "1_000", "12a"].iter() {
+            assert_eq!(Number::parse(invalid, 10), None, "{}", invalid)
+        }
+    }
+
+    #[test]
+    fn test_parse_prefixed_numbers() {
+        let literal = |text| Number::parse_literal(text, 10);
+
+        assert_eq!(literal("#x1F"), Some(Integer(31)));
+        assert_eq!(literal("#X-ff"), Some(Integer(-255)));
+        assert_eq!(literal("#b1010"), Some(Integer(10)));
+        assert_eq!(literal("#o777"), Some(Integer(511)));
+        assert_eq!(literal("#d10"), Some(Integer(10)));
+        assert_eq!(literal("#x1/A").unwrap().to_string(), "1/10");
+        assert_eq!(literal("#e1.5").unwrap().to_string(), "3/2");
+        assert_eq!(literal("#e1.1").unwrap().to_string(), "11/10");
+        assert_eq!(literal("#e-1.5e2"), Some(Integer(-150)));
+        assert_eq!(literal("#e.5e-1").unwrap().to_string(), "1/20");
+        assert_eq!(literal("#e1e3"), Some(Integer(1000)));
+        assert_eq!(literal("#i1/4"), Some(Real(0.25)));
+        assert_eq!(literal("#x#i10"), Some(Real(16.0)));
+        assert_eq!(literal("#e#b101"), Some(Integer(5)));
+        assert_eq!(Number::parse_literal("ff", 16), Some(Integer(255)));
+
+        let invalid_literals = [
+            "#x", "#b102", "#o8", "#x1.5", "#e#i1", "#x#b1", "#e+inf.0", "#q1",
+            "#e1e99999999999999999999", "#e1e100001", "#e1.5e-9999999999"
+        ];
+        for invalid in invalid_literals.iter() {
+            assert_eq!(literal(invalid), None, "{}", invalid)
+        }
+    }
+
+    #[test]
+    fn test_display_numbers_in_radix() {
+        assert_eq!(Integer(255).to_string_radix(16).unwrap(), "ff");
+        assert_eq!(Integer(-5).to_string_radix(2).unwrap(), "-101");
+        assert_eq!(Number::parse("3/8", 10).unwrap().to_string_radix(8).unwrap(), "3/10");
+        assert_eq!(Real(1.5).to_string_radix(10).unwrap(), "1.5");
+        assert_eq!(Real(1.5).to_string_radix(2), None);
+    }
+
+    #[test]
+    fn test_display_numbers() {
+        assert_eq!(Real(3.0).to_string(), "3.0");
+        assert_eq!(Real(-0.25).to_string(), "-0.25");
+        assert_eq!(Real(1e21).to_string(), "1e21");
+        assert_eq!(Real(f64::NAN).to_string(), "+nan.0");
+        assert_eq!(Real(f64::NEG_INFINITY).to_string(), "-inf.0");
+    }
+
+    #[test]
+    fn test_overflow_promotes_to_bignum() {
+        let max = Integer(i64::MAX);
+        assert_eq!(max.add(&Integer(1)), Big(BigInt::from(i64::MAX) + 1));
+        assert_eq!(max.add(&Integer(1)).sub(&Integer(1)), max);
+        assert_eq!(Integer(i64::MIN).mul(&Integer(-1)).to_string(), "9223372036854775808");
+    }
+
+    #[test]
+    fn test_integer_division() {
+        let div_rem = |a: i64, b: i64, floor| {
+            let (quotient, remainder) = Integer(a).div_rem(&Integer(b), floor);
+            (quotient.to_string(), remainder.to_string())
+        };
+
+        assert_eq!(div_rem(7, 2, false), ("3".to_string(), "1".to_string()));
+        assert_eq!(div_rem(-7, 2, false), ("-3".to_string(), "-1".to_string()));
+        assert_eq!(div_rem(-7, 2, true), ("-4".to_string(), "1".to_string()));
+        assert_eq!(div_rem(7, -2, true), ("-4".to_string(), "-1".to_string()));
+        assert_eq!(div_rem(i64::MIN, -1, false).0, "9223372036854775808");
+        assert_eq!(Real(-7.0).div_rem(&Integer(2), true), (Real(-4.0), Real(1.0)));
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(parse("5/2").round(), Integer(2));
+        assert_eq!(parse("7/2").round(), Integer(4));
+        assert_eq!(parse("-5/2").round(), Integer(-2));
+        assert_eq!(parse("-7/3").floor(), Integer(-3));
+        assert_eq!(parse("-7/3").ceiling(), Integer(-2));
+        assert_eq!(parse("-7/3").truncate(), Integer(-2));
+        assert_eq!(Real(2.5).round(), Real(2.0));
+        assert_eq!(Real(-3.5).round(), Real(-4.0));
+        assert_eq!(Integer(7).floor(), Integer(7));
+    }
+
+    #[test]
+    fn test_powers_and_roots() {
+        assert_eq!(
+            Integer(2).expt(&Integer(100)).unwrap().to_string(),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(Integer(2).expt(&Integer(-2)).unwrap().to_string(), "1/4");
+        assert_eq!(Integer(0).expt(&Integer(0)), Ok(Integer(1)));
+        assert_eq!(Integer(0).expt(&Integer(-1)), Err("Division by zero"));
+        assert_eq!(Integer(4).expt(&parse("1/2")), Ok(Real(2.0)));
+        assert_eq!(Real(2.0).expt(&Integer(3)), Ok(Real(8.0)));
+        assert!(Integer(2).expt(&Integer(1 << 19)).is_ok());
+        assert!(Integer(2).expt(&Integer(1_000_000_000_000)).is_err());
+        assert!(parse("1/3").expt(&Integer(-1_000_000_000_000)).is_err());
+        assert_eq!(Integer(-1).expt(&Integer(1_000_000_000_001)), Ok(Integer(-1)));
+        let big = parse("100000000000000000000");
+        assert_eq!(Integer(1).expt(&big), Ok(Integer(1)));
+        assert_eq!(Integer(0).expt(&big), Ok(Integer(0)));
+        assert_eq!(Integer(-1).expt(&big), Ok(Integer(1)));
+        assert_eq!(Integer(-1).expt(&parse("100000000000000000001")), Ok(Integer(-1)));
+        assert_eq!(Integer(-1).expt(&parse("-100000000000000000001")), Ok(Integer(-1)));
+        assert_eq!(Integer(0).expt(&parse("-100000000000000000000")), Err("Division by zero"));
+        assert_eq!(Integer(2).expt(&big), Err("Exact result of 'expt' is too large"));
+        assert_eq!(parse("1/2").expt(&big), Err("Exact result of 'expt' is too large"));
+        assert_eq!(Real(1.0).expt(&big), Ok(Real(1.0)));
+        assert_eq!(Integer(16).sqrt(), Some(Integer(4)));
+        assert_eq!(parse("9/4").sqrt().unwrap().to_string(), "3/2");
+        assert_eq!(Integer(2).sqrt(), Some(Real(2f64.sqrt())));
+        assert_eq!(Integer(-4).sqrt(), None);
+        assert_eq!(Real(-4.0).sqrt(), None);
+        assert_eq!(Integer(17).exact_integer_sqrt(), (Integer(4), Integer(1)));
+        assert_eq!(Integer(12).gcd(&Integer(-18)), Integer(6));
+        assert_eq!(Integer(4).lcm(&Real(6.0)), Real(12.0));
+    }
+
+    #[test]
+    fn test_exactness() {
+        assert_eq!(Integer(1).div(&Integer(3)).unwrap().to_string(), "1/3");
+        assert_eq!(Integer(6).div(&Integer(3)).unwrap(), Integer(2));
+        assert_eq!(Integer(1).div(&Integer(0)), None);
+        assert_eq!(Real(1.0).div(&Integer(0)), None);
+        assert_eq!(Integer(1).div(&Real(0.0)), Some(Real(f64::INFINITY)));
+        assert_eq!(parse("1/2").add(&Real(0.5)), Real(1.0));
+        assert_eq!(Real(0.5).to_exact().unwrap().to_string(), "1/2");
+        assert_eq!(Real(f64::INFINITY).to_exact(), None);
+        assert_eq!(parse("1/4").to_inexact(), Real(0.25));
+    }
+}
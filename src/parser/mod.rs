@@ -1,105 +1,457 @@
 use errors::ParseError;
 use lexer::token::Token;
+use lexer::token::TokenKind;
+use number::Number;
+use span::Span;
 
+use std::fmt;
+use std::rc::Rc;
 use std::slice::Iter;
 
+// A node of the AST and the span of the source it was parsed from.
+#[derive(Clone)]
+pub struct ASTNode {
+    pub kind: NodeKind,
+    pub span: Span,
+}
+
 #[derive(Debug, PartialEq, Clone)]
-pub enum ASTNode {
+pub enum NodeKind {
     Identifier(String),
-    Integer(isize),
+    Number(Number),
     Boolean(bool),
+    Character(char),
     StringNode(String),
-    List(Vec<ASTNode>),
+    List(Rc<Vec<ASTNode>>),
+    // An improper list like `(a b . c)`: the elements before the dot, of
+    // which there is at least one, and the datum after it.
+    DottedList(Rc<Vec<ASTNode>>, Rc<ASTNode>),
+    Vector(Rc<Vec<ASTNode>>),
+    Bytevector(Rc<Vec<u8>>),
+}
+
+use self::NodeKind::*;
+
+impl ASTNode {
+    pub fn new(kind: NodeKind, span: Span) -> ASTNode {
+        ASTNode { kind, span }
+    }
 }
 
-use self::ASTNode::*;
+// Nodes are the same datum wherever they come from, so their spans are left
+// out of comparisons and of the debug output used in error messages.
+impl PartialEq for ASTNode {
+    fn eq(&self, other: &ASTNode) -> bool {
+        self.kind == other.kind
+    }
+}
 
-pub fn parse(tokens: &Vec<Token>) -> Result<Vec<ASTNode>, ParseError> {
-    parse_level(&mut tokens.iter(), 0)
+impl fmt::Debug for ASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.kind)
+    }
 }
 
-fn parse_level(tokens: &mut Iter<Token>, level: u32) -> Result<Vec<ASTNode>, ParseError> {
+pub fn parse(tokens: &[Token]) -> Result<Vec<ASTNode>, ParseError> {
+    let mut tokens = tokens.iter();
     let mut ast_nodes = Vec::new();
 
-    while let Some(token) = tokens.next() {
-        match token {
-            &Token::OpenParen => {
-                let inner = try!(parse_level(tokens, level + 1));
-                ast_nodes.push(List(inner));
+    while let Some(token) = next_token(&mut tokens)? {
+        match token.kind {
+            TokenKind::Dot => return Err(ParseError::UnexpectedDot(token.span.clone())),
+            TokenKind::CloseParen => {
+                return Err(ParseError::ClosingParenWithoutOpening(token.span.clone()))
             },
-            &Token::CloseParen => {
-                if level > 0 {
-                    return Ok(ast_nodes);
-                } else {
-                    return Err(ParseError::ClosingParenWithoutOpening)
-                }
+            _ => ast_nodes.push(parse_datum(token, &mut tokens)?)
+        };
+    }
+
+    Ok(ast_nodes)
+}
+
+// Builds the node starting with the given token, reading the rest of it
+// from the tokens that follow.
+fn parse_datum(token: &Token, tokens: &mut Iter<Token>) -> Result<ASTNode, ParseError> {
+    let kind = match token.kind {
+        TokenKind::OpenParen => return parse_list(token, tokens),
+        TokenKind::OpenVector => {
+            let (nodes, end) = parse_elements(token, tokens)?;
+            return Ok(ASTNode::new(Vector(Rc::new(nodes)), token.span.to(end)))
+        },
+        TokenKind::OpenBytevector => {
+            let (nodes, end) = parse_elements(token, tokens)?;
+            return Ok(ASTNode::new(Bytevector(Rc::new(bytes(&nodes)?)), token.span.to(end)))
+        },
+        TokenKind::CloseParen => {
+            return Err(ParseError::ClosingParenWithoutOpening(token.span.clone()))
+        },
+        TokenKind::Dot => return Err(ParseError::UnexpectedDot(token.span.clone())),
+        TokenKind::DatumComment => unreachable!("datum comments are skipped by next_token"),
+        TokenKind::Quote => return abbreviation("quote", "'", token, tokens),
+        TokenKind::Quasiquote => return abbreviation("quasiquote", "`", token, tokens),
+        TokenKind::Unquote => return abbreviation("unquote", ",", token, tokens),
+        TokenKind::UnquoteSplicing => return abbreviation("unquote-splicing", ",@", token, tokens),
+        TokenKind::Identifier(ref val) => Identifier(val.clone()),
+        TokenKind::Number(ref val) => Number(val.clone()),
+        TokenKind::Boolean(val) => Boolean(val),
+        TokenKind::Character(val) => Character(val),
+        TokenKind::StringToken(ref val) => StringNode(val.clone()),
+    };
+
+    Ok(ASTNode::new(kind, token.span.clone()))
+}
+
+// Reads the elements of a list up to its closing paren. A dot has to come
+// after at least one element and be followed by exactly one more datum.
+fn parse_list(open: &Token, tokens: &mut Iter<Token>) -> Result<ASTNode, ParseError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = next_token(tokens)? {
+        match token.kind {
+            TokenKind::CloseParen => {
+                return Ok(ASTNode::new(List(Rc::new(nodes)), open.span.to(&token.span)))
             },
-            &Token::Identifier(ref val) => {
-                ast_nodes.push(Identifier(val.clone()));
+            TokenKind::Dot if nodes.is_empty() => {
+                return Err(ParseError::UnexpectedDot(token.span.clone()))
             },
-            &Token::Integer(ref val) => {
-                ast_nodes.push(Integer(val.clone()));
+            TokenKind::Dot => {
+                let tail = next_datum(".", token, tokens)?;
+                return match next_token(tokens)? {
+                    Some(close) if close.kind == TokenKind::CloseParen => {
+                        let kind = DottedList(Rc::new(nodes), Rc::new(tail));
+                        Ok(ASTNode::new(kind, open.span.to(&close.span)))
+                    },
+                    Some(_) => Err(ParseError::UnexpectedDot(token.span.clone())),
+                    None => Err(ParseError::UnexpectedEOI(open.span.clone()))
+                }
             },
-            &Token::Boolean(ref val) => {
-                ast_nodes.push(Boolean(val.clone()));
+            _ => nodes.push(parse_datum(token, tokens)?)
+        }
+    }
+
+    Err(ParseError::UnexpectedEOI(open.span.clone()))
+}
+
+// Reads the elements of a vector or a bytevector, returning them with the
+// span of the closing paren.
+fn parse_elements<'a>(
+    open: &Token, tokens: &mut Iter<'a, Token>
+) -> Result<(Vec<ASTNode>, &'a Span), ParseError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = next_token(tokens)? {
+        match token.kind {
+            TokenKind::CloseParen => return Ok((nodes, &token.span)),
+            TokenKind::Dot => return Err(ParseError::UnexpectedDot(token.span.clone())),
+            _ => nodes.push(parse_datum(token, tokens)?)
+        }
+    }
+
+    Err(ParseError::UnexpectedEOI(open.span.clone()))
+}
+
+// Expands `'x`, `` `x ``, `,x` and `,@x` into `(quote x)`, `(quasiquote x)`,
+// `(unquote x)` and `(unquote-splicing x)`.
+fn abbreviation(
+    form: &str, prefix: &str, token: &Token, tokens: &mut Iter<Token>
+) -> Result<ASTNode, ParseError> {
+    let datum = next_datum(prefix, token, tokens)?;
+    let span = token.span.to(&datum.span);
+    let form = ASTNode::new(Identifier(form.to_string()), token.span.clone());
+    Ok(ASTNode::new(List(Rc::new(vec![form, datum])), span))
+}
+
+// Reads the datum which has to follow a prefix like `'`, `.` or `#;`, given
+// with its token.
+fn next_datum(
+    prefix: &str, prefix_token: &Token, tokens: &mut Iter<Token>
+) -> Result<ASTNode, ParseError> {
+    match next_token(tokens)? {
+        Some(token) if token.kind != TokenKind::CloseParen => parse_datum(token, tokens),
+        _ => Err(ParseError::MissingDatum(prefix.to_string(), prefix_token.span.clone()))
+    }
+}
+
+// The next token which isn't commented out. The datum after a `#;` is
+// parsed as usual, so it has to be well formed, and then dropped.
+fn next_token<'a>(tokens: &mut Iter<'a, Token>) -> Result<Option<&'a Token>, ParseError> {
+    loop {
+        match tokens.next() {
+            Some(token) if token.kind == TokenKind::DatumComment => {
+                next_datum("#;", token, tokens)?;
             },
-            &Token::StringToken(ref val) => {
-                ast_nodes.push(StringNode(val.clone()));
+            token => return Ok(token)
+        }
+    }
+}
+
+fn bytes(nodes: &[ASTNode]) -> Result<Vec<u8>, ParseError> {
+    let mut result = Vec::with_capacity(nodes.len());
+
+    for node in nodes.iter() {
+        let element = match node.kind {
+            Number(Number::Integer(x)) if (0..=255).contains(&x) => {
+                result.push(x as u8);
+                continue
             },
+            Number(ref x) => x.to_string(),
+            Identifier(ref x) => x.clone(),
+            _ => format!("{:?}", node)
         };
+        return Err(ParseError::InvalidByte(element, node.span.clone()))
     }
 
-    if level == 0 {
-        Ok(ast_nodes)
-    } else {
-        Err(ParseError::UnexpectedEOI)
-    }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
-    use lexer::token::Token;
+    use std::rc::Rc;
+
+    use errors::ParseError;
+    use lexer::token::TokenKind as Token;
+    use lexer::tokenize;
+    use number::Number;
+    use span::Span;
 
     use super::ASTNode;
-    use super::ASTNode::*;
-    use super::parse;
+    use super::NodeKind;
+    use super::NodeKind::*;
 
+    // Parses tokens of the given kinds, laid out on the first line one
+    // character apart, so that errors point at the position of the token.
+    fn parse(kinds: &[Token]) -> Result<Vec<ASTNode>, ParseError> {
+        let tokens = kinds.iter().enumerate().map(|(index, kind)| {
+            let span = Span { line: 1, column: index, start: index, end: index + 1, ..Span::default() };
+            super::Token { kind: kind.clone(), span }
+        });
+        super::parse(&tokens.collect::<Vec<_>>())
+    }
+
+    fn node(kind: NodeKind) -> ASTNode { ASTNode::new(kind, Span::default()) }
     fn id_token(id: &str) -> Token { Token::Identifier(id.to_string()) }
-    fn id(id: &str) -> ASTNode { ASTNode::Identifier(id.to_string()) }
+    fn id(id: &str) -> ASTNode { node(Identifier(id.to_string())) }
+    fn list(nodes: Vec<ASTNode>) -> ASTNode { node(List(Rc::new(nodes))) }
+    fn int_token(n: i64) -> Token { Token::Number(Number::Integer(n)) }
+    fn int(n: i64) -> ASTNode { node(NodeKind::Number(Number::Integer(n))) }
 
     #[test]
     fn test_parse() {
         assert_eq!(
             parse(
-                &vec![
-                    Token::OpenParen, id_token("+"), Token::Integer(1),
-                    Token::Integer(2), Token::CloseParen
+                &[
+                    Token::OpenParen, id_token("+"), int_token(1),
+                    int_token(2), Token::CloseParen
                 ]
             ).unwrap(),
-            vec![List(vec![id("+"), Integer(1), Integer(2)])]
+            vec![list(vec![id("+"), int(1), int(2)])]
         );
 
         assert_eq!(
             parse(
-                &vec![
-                    Token::OpenParen, id_token("+"), Token::Integer(1),
-                    Token::OpenParen, id_token("-"), Token::Integer(5),
-                    Token::Integer(4), Token::CloseParen, Token::CloseParen
+                &[
+                    Token::OpenParen, id_token("+"), int_token(1),
+                    Token::OpenParen, id_token("-"), int_token(5),
+                    int_token(4), Token::CloseParen, Token::CloseParen
                 ]
             ).unwrap(),
             vec![
-                List(
+                list(
                     vec![
-                        id("+"), Integer(1),
-                        List(vec![id("-"), Integer(5), Integer(4)])
+                        id("+"), int(1),
+                        list(vec![id("-"), int(5), int(4)])
                     ]
                 )
             ]
         );
+
+        assert_eq!(
+            parse(&[Token::OpenParen, Token::OpenParen, Token::CloseParen]).err().unwrap().to_string(),
+            "ParseError: Unexpected end of input, a paren is not closed (line: 1, column: 1)"
+        );
+        assert_eq!(
+            parse(&[int_token(1), Token::CloseParen]).err().unwrap().to_string(),
+            "ParseError: Closing parens don't match the opening ones (line: 1, column: 2)"
+        );
+    }
+
+    #[test]
+    fn test_parse_vectors() {
+        assert_eq!(
+            parse(
+                &[
+                    Token::OpenVector, int_token(1), Token::OpenParen, id_token("a"),
+                    Token::CloseParen, Token::OpenVector, Token::CloseParen, Token::CloseParen
+                ]
+            ).unwrap(),
+            vec![node(Vector(Rc::new(vec![int(1), list(vec![id("a")]), node(Vector(Rc::new(vec![])))])))]
+        );
+
+        assert!(parse(&[Token::OpenVector, int_token(1)]).is_err());
+    }
+
+    #[test]
+    fn test_parse_bytevectors() {
+        let bytevector = |tokens: Vec<Token>| {
+            let mut all = vec![Token::OpenBytevector];
+            all.extend(tokens);
+            all.push(Token::CloseParen);
+            parse(&all)
+        };
+
+        assert_eq!(
+            bytevector(vec![int_token(0), int_token(255)]).unwrap(),
+            vec![node(Bytevector(Rc::new(vec![0, 255])))]
+        );
+
+        assert_eq!(
+            bytevector(vec![int_token(256)]).err().unwrap().to_string(),
+            "ParseError: Bytevector elements must be integers from 0 to 255: 256 (line: 1, column: 2)"
+        );
+        assert!(bytevector(vec![id_token("a")]).is_err());
+        assert!(bytevector(vec![Token::OpenParen, Token::CloseParen]).is_err());
+    }
+
+    #[test]
+    fn test_parse_quotes() {
+        let quoted = |form: &str, node: ASTNode| list(vec![id(form), node]);
+
+        assert_eq!(
+            parse(&[Token::Quote, id_token("a"), Token::Quasiquote, Token::Quote, int_token(1)])
+                .unwrap(),
+            vec![quoted("quote", id("a")), quoted("quasiquote", quoted("quote", int(1)))]
+        );
+
+        assert_eq!(
+            parse(
+                &[
+                    Token::OpenParen, Token::Unquote, id_token("a"), Token::UnquoteSplicing,
+                    Token::OpenParen, Token::CloseParen, Token::CloseParen
+                ]
+            ).unwrap(),
+            vec![list(vec![quoted("unquote", id("a")), quoted("unquote-splicing", list(vec![]))])]
+        );
+
+        assert_eq!(
+            parse(&[Token::OpenParen, Token::Quote, Token::CloseParen]).err().unwrap().to_string(),
+            "ParseError: Expected a datum after ' (line: 1, column: 2)"
+        );
+        assert!(parse(&[Token::Quote]).is_err());
+    }
+
+    #[test]
+    fn test_parse_dotted_lists() {
+        let dotted = |nodes: Vec<ASTNode>, tail: ASTNode| {
+            node(DottedList(Rc::new(nodes), Rc::new(tail)))
+        };
+        let in_list = |tokens: Vec<Token>| {
+            let mut all = vec![Token::OpenParen];
+            all.extend(tokens);
+            all.push(Token::CloseParen);
+            parse(&all)
+        };
+
+        assert_eq!(
+            in_list(vec![id_token("a"), Token::Dot, int_token(1)]).unwrap(),
+            vec![dotted(vec![id("a")], int(1))]
+        );
+        assert_eq!(
+            in_list(
+                vec![
+                    id_token("a"), id_token("b"), Token::Dot, Token::OpenParen,
+                    id_token("c"), Token::Dot, id_token("d"), Token::CloseParen
+                ]
+            ).unwrap(),
+            vec![dotted(vec![id("a"), id("b")], dotted(vec![id("c")], id("d")))]
+        );
+
+        let error = |tokens: Vec<Token>| in_list(tokens).err().unwrap().to_string();
+        let unexpected = |column: usize| format!(
+            "ParseError: Unexpected '.', it can only come before the last datum of a list \
+             (line: 1, column: {})",
+            column
+        );
+        assert_eq!(error(vec![Token::Dot, id_token("a")]), unexpected(2));
+        assert_eq!(error(vec![id_token("a"), Token::Dot, int_token(1), int_token(2)]), unexpected(3));
+        assert_eq!(error(vec![id_token("a"), Token::Dot, Token::Dot, int_token(1)]), unexpected(4));
+        assert_eq!(
+            error(vec![id_token("a"), Token::Dot]),
+            "ParseError: Expected a datum after . (line: 1, column: 3)"
+        );
+        assert_eq!(parse(&[Token::Dot]).err().unwrap().to_string(), unexpected(1));
+        assert_eq!(
+            parse(&[Token::OpenVector, int_token(1), Token::Dot, int_token(2), Token::CloseParen])
+                .err().unwrap().to_string(),
+            unexpected(3)
+        );
+        assert!(parse(&[Token::OpenParen, id_token("a"), Token::Dot, id_token("b")]).is_err());
+    }
+
+    #[test]
+    fn test_parse_datum_comments() {
+        assert_eq!(
+            parse(
+                &[
+                    Token::DatumComment, int_token(1), Token::OpenParen, id_token("a"),
+                    Token::DatumComment, Token::OpenParen, id_token("b"), Token::CloseParen,
+                    Token::CloseParen, int_token(2)
+                ]
+            ).unwrap(),
+            vec![list(vec![id("a")]), int(2)]
+        );
+
+        assert_eq!(
+            parse(
+                &[
+                    Token::DatumComment, Token::DatumComment, int_token(1), int_token(2),
+                    Token::Quote, Token::DatumComment, int_token(3), int_token(4)
+                ]
+            ).unwrap(),
+            vec![list(vec![id("quote"), int(4)])]
+        );
+
+        assert_eq!(
+            parse(
+                &[
+                    Token::OpenParen, id_token("a"), Token::Dot, Token::DatumComment,
+                    id_token("b"), id_token("c"), Token::DatumComment, int_token(1),
+                    Token::CloseParen
+                ]
+            ).unwrap(),
+            vec![node(DottedList(Rc::new(vec![id("a")]), Rc::new(id("c"))))]
+        );
+
+        assert_eq!(
+            parse(&[Token::OpenParen, Token::DatumComment, Token::CloseParen])
+                .err().unwrap().to_string(),
+            "ParseError: Expected a datum after #; (line: 1, column: 2)"
+        );
+        assert!(parse(&[Token::DatumComment]).is_err());
+        assert!(parse(&[Token::DatumComment, Token::OpenParen]).is_err());
+    }
+
+    #[test]
+    fn test_node_spans() {
+        let nodes = super::parse(&tokenize("(a\n 'b #(1))").unwrap()).unwrap();
+        let span = |node: &ASTNode| (node.span.line, node.span.column, node.span.start, node.span.end);
+
+        assert_eq!(span(&nodes[0]), (1, 0, 0, 12));
+        match nodes[0].kind {
+            List(ref elements) => {
+                assert_eq!(elements.iter().map(span).collect::<Vec<_>>(), vec![
+                    (1, 1, 1, 2), (2, 1, 4, 6), (2, 4, 7, 11)
+                ]);
+                match elements[1].kind {
+                    List(ref quoted) => assert_eq!(span(&quoted[0]), (2, 1, 4, 5)),
+                    _ => panic!("Expected a quote form: {:?}", elements[1])
+                }
+            },
+            _ => panic!("Expected a list: {:?}", nodes[0])
+        }
     }
 
     #[test]
     fn test_parse_err() {
-        assert!(parse(&vec![Token::OpenParen, id_token("+")]).is_err())
+        assert!(parse(&[Token::OpenParen, id_token("+")]).is_err())
     }
 }
@@ -1,6 +1,10 @@
 use rustyline::Editor;
 use rustyline::error::ReadlineError;
 
+use std::cell::Cell;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
 use interpreter::runtime::Runtime;
 use interpreter::runtime::RuntimeNode;
 
@@ -29,18 +33,54 @@ fn ask(prompt: &str, rl: &mut Editor<()>) -> Option<String> {
     }
 }
 
+thread_local! {
+    // Whether a panic on this thread is caught and reported by evaluate.
+    static CATCHING_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+// Evaluates the input, turning a panic during the evaluation into an error
+// so that a bug in the interpreter doesn't end the session. The runtime
+// stays usable, keeping every definition made before the panic.
+fn evaluate<F>(f: &F, input: String, runtime: &RuntimeNode) -> Result<String, String>
+    where F: Fn(String, &RuntimeNode) -> Result<String, String> {
+    let was_catching = CATCHING_PANICS.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(input, runtime)));
+    CATCHING_PANICS.with(|catching| catching.set(was_catching));
+
+    match result {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            Err(format!("InternalError: {}", message))
+        }
+    }
+}
+
 pub fn start<F: Fn(String, &RuntimeNode) -> Result<String, String>>(prompt: &str, f: F) {
     let mut rl = Editor::<()>::new();
     let runtime = &Runtime::new();
 
+    // Panics caught by evaluate are reported by it, the previous hook would
+    // print them a second time. Any other panic still goes through it.
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !CATCHING_PANICS.with(|catching| catching.get()) {
+            previous_hook(info)
+        }
+    }));
+
     loop {
         match ask(prompt, &mut rl) {
             Some(input) => {
-                if input.len() > 0 {
-                    match f(input, runtime) {
+                if !input.is_empty() {
+                    match evaluate(&f, input, runtime) {
                         Ok(result) => {
-                            let output = format!("{}", result);
-                            if output.contains("BadIndex") {
+                            let output = result;
+                            if output.is_empty() {
+                                continue
+                            } else if output.contains("BadIndex") {
                                 println!("{}", "Error: invalid term".red())
                             } else {
                                 println!("{}", output.yellow())
@@ -54,3 +94,30 @@ pub fn start<F: Fn(String, &RuntimeNode) -> Result<String, String>>(prompt: &str
         };
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use interpreter::run;
+    use interpreter::runtime::Runtime;
+    use interpreter::runtime::RuntimeNode;
+
+    use super::evaluate;
+    use super::CATCHING_PANICS;
+
+    #[test]
+    fn test_evaluate_survives_panics() {
+        let runtime = &Runtime::new();
+        let f = |input: String, runtime: &RuntimeNode| {
+            if input == "crash" { panic!("crashed on {}", input) }
+            run(&input, runtime)
+        };
+
+        assert_eq!(evaluate(&f, "(define x 41)".to_string(), runtime), Ok(String::new()));
+        assert_eq!(
+            evaluate(&f, "crash".to_string(), runtime),
+            Err("InternalError: crashed on crash".to_string())
+        );
+        assert!(!CATCHING_PANICS.with(|catching| catching.get()));
+        assert_eq!(evaluate(&f, "(+ x 1)".to_string(), runtime), Ok("42".to_string()));
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+use std::rc::Rc;
+
+// Where a token or an AST node comes from: the name of its source, the line
+// and the column it starts at and its byte range in the source. Lines are
+// counted from 1 and columns, in characters, from 0, the same way as in
+// syntax errors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span {
+    pub file: Rc<String>,
+    pub line: usize,
+    pub column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    // The span from the start of this one to the end of the other.
+    pub fn to(&self, other: &Span) -> Span {
+        Span { end: other.end, ..self.clone() }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line: {}, column: {}", self.line, self.column + 1)
+    }
+}